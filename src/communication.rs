@@ -0,0 +1,138 @@
+//! Communication costs of arcs.
+//!
+//! The type of an arc is an index into a communication table, which is a data
+//! table whose `type` column lists arc types and whose other columns give the
+//! corresponding data volumes or transfer times.
+
+use std::collections::HashMap;
+
+use {Arc, Content};
+
+/// The quantity stored in a communication table.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Quantity {
+    /// The amount of data transferred along an arc.
+    Volume,
+    /// The time needed to transfer the data along an arc.
+    Time,
+}
+
+/// A communication model.
+///
+/// Arc types that are not present in the table are assumed to transfer a unit
+/// volume of data, and volumes are converted into times using a constant
+/// bandwidth.
+#[derive(Clone, Debug)]
+pub struct Communication {
+    /// The amount of data transferred per unit of time.
+    pub bandwidth: f64,
+    quantity: Quantity,
+    values: HashMap<usize, f64>,
+}
+
+impl Communication {
+    /// Create a model without a table.
+    ///
+    /// Every arc transfers a unit volume of data at the given bandwidth.
+    pub fn new(bandwidth: f64) -> Communication {
+        Communication {
+            bandwidth: bandwidth,
+            quantity: Quantity::Volume,
+            values: HashMap::new(),
+        }
+    }
+
+    /// Create a model from a table.
+    ///
+    /// The first table called `name` is taken, and the values of arc types are
+    /// read from the column called `column`. The function returns `None` if
+    /// the table or any of the needed columns is missing.
+    pub fn from_table(content: &Content, name: &str, column: &str, quantity: Quantity,
+                      bandwidth: f64) -> Option<Communication> {
+
+        let table = match content.tables.iter().find(|table| table.name == name) {
+            Some(table) => table,
+            _ => return None,
+        };
+        let kinds = match table.columns.iter().find(|column| column.name == "type") {
+            Some(column) => column,
+            _ => return None,
+        };
+        let data = match table.columns.iter().find(|other| other.name == column) {
+            Some(column) => column,
+            _ => return None,
+        };
+
+        let mut values = HashMap::new();
+        for (&kind, &value) in kinds.data.iter().zip(data.data.iter()) {
+            values.insert(kind as usize, value);
+        }
+
+        Some(Communication { bandwidth: bandwidth, quantity: quantity, values: values })
+    }
+
+    /// Return the volume of data transferred by an arc of a particular type.
+    pub fn volume(&self, kind: usize) -> f64 {
+        match (self.values.get(&kind), self.quantity) {
+            (Some(&value), Quantity::Volume) => value,
+            (Some(&value), Quantity::Time) => value * self.bandwidth,
+            _ => 1.0,
+        }
+    }
+
+    /// Return the time needed to transfer the data of an arc of a particular
+    /// type between two distinct processing elements.
+    pub fn time(&self, kind: usize) -> f64 {
+        match (self.values.get(&kind), self.quantity) {
+            (Some(&value), Quantity::Time) => value,
+            (Some(&value), Quantity::Volume) => value / self.bandwidth,
+            _ => 1.0 / self.bandwidth,
+        }
+    }
+
+    /// Return the cost of an arc whose source and destination tasks are mapped
+    /// onto the processing elements `from` and `to`, respectively.
+    ///
+    /// The cost is zero when both tasks share the same processing element.
+    #[inline]
+    pub fn cost(&self, arc: &Arc, from: usize, to: usize) -> f64 {
+        if from == to { 0.0 } else { self.time(arc.kind) }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Communication, Quantity};
+
+    const INPUT: &str = "@COMMUN 0 {\n# \n#----\n# type volume\n 0 4\n 1 8\n}\n";
+
+    #[test]
+    fn from_table() {
+        let content = ::parse(INPUT).unwrap();
+
+        let model = Communication::from_table(&content, "COMMUN", "volume",
+                                              Quantity::Volume, 2.0).unwrap();
+        assert_eq!(model.volume(1), 8.0);
+        assert_eq!(model.time(0), 2.0);
+        assert_eq!(model.time(1), 4.0);
+        assert_eq!(model.time(42), 0.5);
+
+        let model = Communication::from_table(&content, "COMMUN", "volume",
+                                              Quantity::Time, 2.0).unwrap();
+        assert_eq!(model.volume(1), 16.0);
+        assert_eq!(model.time(1), 8.0);
+
+        assert!(Communication::from_table(&content, "CORE", "volume",
+                                          Quantity::Time, 1.0).is_none());
+        assert!(Communication::from_table(&content, "COMMUN", "time",
+                                          Quantity::Time, 1.0).is_none());
+    }
+
+    #[test]
+    fn cost() {
+        let model = Communication::new(4.0);
+        let arc = ::content::new_arc(0, 0, 1, 7);
+        assert_eq!(model.cost(&arc, 0, 1), 0.25);
+        assert_eq!(model.cost(&arc, 1, 1), 0.0);
+    }
+}
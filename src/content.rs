@@ -1,10 +1,17 @@
 #![allow(missing_copy_implementations)]
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize, Serializer};
 
 /// The content of a TGFF file.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Content {
     /// Global attributes such as `HYPERPERIOD`.
+    #[cfg_attr(feature = "serde", serde(serialize_with = "sorted"))]
     pub attributes: HashMap<String, usize>,
     /// Task graphs.
     pub graphs: Vec<Graph>,
@@ -13,12 +20,15 @@ pub struct Content {
 }
 
 /// A task graph.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Graph {
     /// The name of the graph.
     pub name: String,
     /// The ID of the graph.
     pub id: usize,
     /// Graph attributes such as `PERIOD`.
+    #[cfg_attr(feature = "serde", serde(serialize_with = "sorted"))]
     pub attributes: HashMap<String, usize>,
     /// Vertices representing tasks.
     pub tasks: Vec<Task>,
@@ -29,6 +39,8 @@ pub struct Graph {
 }
 
 /// A node in a graph representing a task.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Task {
     /// The ID of the task.
     pub id: usize,
@@ -37,6 +49,8 @@ pub struct Task {
 }
 
 /// An edge in a graph connecting two tasks.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Arc {
     /// The ID of the arc.
     pub id: usize,
@@ -49,6 +63,8 @@ pub struct Arc {
 }
 
 /// The deadline of a task.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Deadline {
     /// The ID of the deadline.
     pub id: usize,
@@ -59,18 +75,23 @@ pub struct Deadline {
 }
 
 /// A data table.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Table {
     /// The name of the table.
     pub name: String,
     /// The ID of the table.
     pub id: usize,
     /// Table attributes.
+    #[cfg_attr(feature = "serde", serde(serialize_with = "sorted"))]
     pub attributes: HashMap<String, f64>,
     /// The columns of the table.
     pub columns: Vec<Column>,
 }
 
 /// A column of a table.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Column {
     /// The name of the column.
     pub name: String,
@@ -78,6 +99,113 @@ pub struct Column {
     pub data: Vec<f64>,
 }
 
+impl fmt::Display for Content {
+    /// Write the content in the TGFF format.
+    ///
+    /// Attributes are written in the order of their names, and the names of
+    /// tasks, arcs and deadlines are derived from their IDs and the IDs of
+    /// their graphs as in `t0_1`. The attribute block of a table is omitted if
+    /// the table has no attributes. If the columns of a table differ in
+    /// length, the missing values of the shorter ones are left blank, and such
+    /// a table cannot be read back.
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        let mut sections = vec![];
+        for (name, value) in self.attributes.iter().collect::<BTreeMap<_, _>>() {
+            sections.push(format!("@{} {}\n", name, value));
+        }
+        sections.extend(self.graphs.iter().map(|graph| graph.to_string()));
+        sections.extend(self.tables.iter().map(|table| table.to_string()));
+        write!(formatter, "{}", sections.join("\n"))
+    }
+}
+
+impl fmt::Display for Graph {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        let id = self.id;
+        writeln!(formatter, "@{} {} {{", self.name, id)?;
+        for (name, value) in self.attributes.iter().collect::<BTreeMap<_, _>>() {
+            writeln!(formatter, "\t{} {}", name, value)?;
+        }
+        if !self.attributes.is_empty() {
+            writeln!(formatter)?;
+        }
+        for task in self.tasks.iter() {
+            writeln!(formatter, "\tTASK t{}_{}\tTYPE {}", id, task.id, task.kind)?;
+        }
+        if !self.arcs.is_empty() {
+            writeln!(formatter)?;
+        }
+        for arc in self.arcs.iter() {
+            writeln!(formatter, "\tARC a{}_{} \tFROM t{}_{}  TO  t{}_{} TYPE {}", id, arc.id, id,
+                     arc.from, id, arc.to, arc.kind)?;
+        }
+        if !self.deadlines.is_empty() {
+            writeln!(formatter)?;
+        }
+        for deadline in self.deadlines.iter() {
+            writeln!(formatter, "\tHARD_DEADLINE d{}_{} ON t{}_{} AT {}", id, deadline.id, id,
+                     deadline.on, deadline.at)?;
+        }
+        writeln!(formatter, "}}")
+    }
+}
+
+impl fmt::Display for Table {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        let rows = self.columns.iter().map(|column| column.data.len()).max().unwrap_or(0);
+        writeln!(formatter, "@{} {} {{", self.name, self.id)?;
+        if !self.attributes.is_empty() {
+            let attributes = self.attributes.iter().collect::<BTreeMap<_, _>>();
+            let names = attributes.keys().map(|name| format!(" {}", name)).collect::<String>();
+            writeln!(formatter, "#{}", names)?;
+            let values = attributes.values().map(|value| format!(" {}", value))
+                                            .collect::<String>();
+            writeln!(formatter, "{}", format!(" {}", values).trim_end())?;
+            writeln!(formatter)?;
+        }
+
+        writeln!(formatter, "#{}", "-".repeat(78))?;
+        let values = self.columns.iter().map(|column| {
+            column.data.iter().map(|value| value.to_string()).collect::<Vec<_>>()
+        }).collect::<Vec<_>>();
+        let widths = self.columns.iter().zip(values.iter()).map(|(column, values)| {
+            values.iter().map(|value| value.len()).fold(column.name.len(), usize::max)
+        }).collect::<Vec<_>>();
+        let line = |start: &str, cells: Vec<&str>| {
+            let mut line = String::from(start);
+            for (cell, &width) in cells.into_iter().zip(widths.iter()) {
+                line.push_str(&format!(" {:1$}", cell, width));
+            }
+            line.trim_end().to_string()
+        };
+        writeln!(formatter, "{}", line("#", self.columns.iter().map(|column| &column.name[..])
+                                                                 .collect()))?;
+        for i in 0..rows {
+            writeln!(formatter, "{}", line(" ", values.iter().map(|values| {
+                values.get(i).map(|value| &value[..]).unwrap_or("")
+            }).collect()))?;
+        }
+        writeln!(formatter, "}}")
+    }
+}
+
+#[cfg(feature = "serde")]
+fn sorted<S, T>(map: &HashMap<String, T>, serializer: S) -> Result<S::Ok, S::Error>
+    where S: Serializer, T: Serialize
+{
+    map.iter().collect::<BTreeMap<_, _>>().serialize(serializer)
+}
+
+/// Return the number of rows of a table unless its columns differ in length.
+pub fn rows(table: &Table) -> Option<usize> {
+    let count = table.columns.first().map(|column| column.data.len()).unwrap_or(0);
+    if table.columns.iter().all(|column| column.data.len() == count) {
+        Some(count)
+    } else {
+        None
+    }
+}
+
 #[inline]
 pub fn new() -> Content {
     Content {
@@ -0,0 +1,267 @@
+//! Comma-separated values of data tables.
+//!
+//! A table is written as a header with the names of its columns followed by
+//! one line per row. The attributes of the table are written either as a
+//! preamble of lines starting with `#`, such as `# price,10.5042`, or as a
+//! separate sidecar with one attribute per line, such as `price,10.5042`.
+
+use std::fmt::Write;
+
+use {Error, Result, Table};
+use content::{new_column, new_table};
+
+/// A configuration of reading and writing.
+#[derive(Clone, Copy, Debug)]
+pub struct Config {
+    /// The character separating values.
+    pub delimiter: char,
+    /// The writing of the attributes as a preamble.
+    pub preamble: bool,
+}
+
+impl Default for Config {
+    fn default() -> Config {
+        Config { delimiter: ',', preamble: true }
+    }
+}
+
+/// Write a table.
+///
+/// Values are written in the shortest form that reads back to the same
+/// number. If the columns differ in length, the missing values at the ends of
+/// the shorter columns are left empty, which `read` accepts.
+pub fn write(table: &Table, config: &Config) -> String {
+    let mut output = String::new();
+    if config.preamble {
+        for (name, value) in sorted(table) {
+            writeln!(output, "# {}{}{}", quote(name, config), config.delimiter, value).unwrap();
+        }
+    }
+    let names = table.columns.iter().map(|column| quote(&column.name, config))
+                                    .collect::<Vec<_>>();
+    writeln!(output, "{}", names.join(&config.delimiter.to_string())).unwrap();
+    let rows = table.columns.iter().map(|column| column.data.len()).max().unwrap_or(0);
+    for i in 0..rows {
+        let values = table.columns.iter().map(|column| {
+            column.data.get(i).map(|value| value.to_string()).unwrap_or_default()
+        }).collect::<Vec<_>>();
+        writeln!(output, "{}", values.join(&config.delimiter.to_string())).unwrap();
+    }
+    output
+}
+
+/// Write the attributes of a table as a sidecar.
+pub fn write_attributes(table: &Table, config: &Config) -> String {
+    let mut output = String::new();
+    for (name, value) in sorted(table) {
+        writeln!(output, "{}{}{}", quote(name, config), config.delimiter, value).unwrap();
+    }
+    output
+}
+
+/// Read a table with a particular name and ID.
+///
+/// Lines starting with `#` before the header are read as attributes, and
+/// empty lines are skipped. Names can be enclosed in double quotes, in which
+/// case a double quote is escaped by another. Every row should have as many
+/// values as there are columns. A value can be empty, which marks the end of
+/// a column shorter than the others, in which case the column should have no
+/// values in the subsequent rows.
+pub fn read(input: &str, name: &str, id: usize, config: &Config) -> Result<Table> {
+    let mut table = new_table(name.to_string(), id);
+    let mut ended = vec![];
+    let mut header = false;
+    for (i, text) in input.lines().enumerate() {
+        let line = i + 1;
+        if text.trim().is_empty() {
+            continue;
+        }
+        if !header && text.starts_with('#') {
+            let (name, value) = attribute(&text[1..], line, config)?;
+            table.attributes.insert(name, value);
+            continue;
+        }
+        let fields = split(text, line, config)?;
+        if !header {
+            for name in fields {
+                table.columns.push(new_column(name));
+            }
+            ended = vec![false; table.columns.len()];
+            header = true;
+            continue;
+        }
+        if fields.len() != table.columns.len() {
+            raise!(line, "expected {} values but found {}", table.columns.len(), fields.len());
+        }
+        for (k, field) in fields.iter().enumerate() {
+            let column = &mut table.columns[k];
+            if field.is_empty() {
+                ended[k] = true;
+            } else if ended[k] {
+                raise!(line, "found a value after the end of the column `{}`", column.name);
+            } else {
+                column.data.push(number(field, line)?);
+            }
+        }
+    }
+    Ok(table)
+}
+
+/// Read the attributes of a table from a sidecar.
+pub fn read_attributes(input: &str, table: &mut Table, config: &Config) -> Result<()> {
+    for (i, text) in input.lines().enumerate() {
+        if text.trim().is_empty() {
+            continue;
+        }
+        let (name, value) = attribute(text, i + 1, config)?;
+        table.attributes.insert(name, value);
+    }
+    Ok(())
+}
+
+fn attribute(text: &str, line: usize, config: &Config) -> Result<(String, f64)> {
+    let mut fields = split(text, line, config)?;
+    if fields.len() != 2 {
+        raise!(line, "expected a name and a value but found {} values", fields.len());
+    }
+    let value = number(&fields[1], line)?;
+    Ok((fields.swap_remove(0), value))
+}
+
+fn split(text: &str, line: usize, config: &Config) -> Result<Vec<String>> {
+    let mut fields = vec![];
+    let mut chars = text.chars().peekable();
+    loop {
+        while chars.peek() == Some(&' ') {
+            chars.next();
+        }
+        let mut field = String::new();
+        if chars.peek() == Some(&'"') {
+            chars.next();
+            loop {
+                match chars.next() {
+                    Some('"') if chars.peek() == Some(&'"') => {
+                        chars.next();
+                        field.push('"');
+                    },
+                    Some('"') => break,
+                    Some(c) => field.push(c),
+                    _ => raise!(line, "found an unterminated quote"),
+                }
+            }
+            while chars.peek() == Some(&' ') {
+                chars.next();
+            }
+            match chars.peek() {
+                Some(&c) if c == config.delimiter => {},
+                None => {},
+                _ => raise!(line, "expected a delimiter after a quoted value"),
+            }
+        } else {
+            while let Some(&c) = chars.peek() {
+                if c == config.delimiter {
+                    break;
+                }
+                field.push(c);
+                chars.next();
+            }
+            field = field.trim().to_string();
+        }
+        fields.push(field);
+        if chars.next().is_none() {
+            return Ok(fields);
+        }
+    }
+}
+
+fn number(field: &str, line: usize) -> Result<f64> {
+    match field.parse() {
+        Ok(value) => Ok(value),
+        _ => raise!(line, "failed to parse `{}` as a number", field),
+    }
+}
+
+fn quote(name: &str, config: &Config) -> String {
+    if name.contains(config.delimiter) || name.contains('"') || name.starts_with('#') ||
+       name.trim() != name {
+        format!("\"{}\"", name.replace('"', "\"\""))
+    } else {
+        name.to_string()
+    }
+}
+
+fn sorted(table: &Table) -> Vec<(&String, &f64)> {
+    let mut attributes = table.attributes.iter().collect::<Vec<_>>();
+    attributes.sort_by(|one, other| one.0.cmp(other.0));
+    attributes
+}
+
+#[cfg(test)]
+mod tests {
+    use content::{new_column, new_table};
+    use super::Config;
+
+    #[test]
+    fn write() {
+        let mut table = new_table(String::from("CORE"), 0);
+        table.attributes.insert(String::from("price"), 10.5042);
+        table.attributes.insert(String::from("area"), 2.0);
+        let mut column = new_column(String::from("type"));
+        column.data = vec![0.0, 1.0, 2.0];
+        table.columns.push(column);
+        let mut column = new_column(String::from("execution time, s"));
+        column.data = vec![0.025, 1e-7, 1.0 / 3.0];
+        table.columns.push(column);
+
+        let config = Config::default();
+        let output = super::write(&table, &config);
+        assert_eq!(output, "# area,2
+# price,10.5042
+type,\"execution time, s\"
+0,0.025
+1,0.0000001
+2,0.3333333333333333
+");
+        assert_eq!(super::read(&output, "CORE", 0, &config).unwrap(), table);
+
+        let config = Config { delimiter: ';', preamble: false };
+        let output = super::write(&table, &config);
+        assert_eq!(output.lines().next(), Some("type;execution time, s"));
+        let sidecar = super::write_attributes(&table, &config);
+        assert_eq!(sidecar, "area;2\nprice;10.5042\n");
+        let mut result = super::read(&output, "CORE", 0, &config).unwrap();
+        assert!(result.attributes.is_empty());
+        super::read_attributes(&sidecar, &mut result, &config).unwrap();
+        assert_eq!(result, table);
+    }
+
+    #[test]
+    fn read() {
+        let config = Config::default();
+        let table = super::read("\r\n \"a \"\"b\"\" \" , c\r\n1, 2.5e1\r\n\r\n-3,4\r\n",
+                                "TABLE", 7, &config).unwrap();
+        assert_eq!((&table.name[..], table.id), ("TABLE", 7));
+        assert_eq!(table.columns[0].name, "a \"b\" ");
+        assert_eq!(table.columns[1].name, "c");
+        assert_eq!(table.columns[0].data, &[1.0, -3.0]);
+        assert_eq!(table.columns[1].data, &[25.0, 4.0]);
+
+        let table = super::read("a,b\n1,2\n3,\n", "TABLE", 0, &config).unwrap();
+        assert_eq!(table.columns[0].data, &[1.0, 3.0]);
+        assert_eq!(table.columns[1].data, &[2.0]);
+        assert_eq!(super::read(&super::write(&table, &config), "TABLE", 0, &config).unwrap(),
+                   table);
+        let error = super::read("a,b\n1,\n3,4\n", "TABLE", 0, &config).err().unwrap();
+        assert_eq!(error.line, 3);
+        assert_eq!(error.message, "found a value after the end of the column `b`");
+
+        let error = super::read("a,b\n1,2\n3\n", "TABLE", 0, &config).err().unwrap();
+        assert_eq!(error.line, 3);
+        assert_eq!(error.message, "expected 2 values but found 1");
+        let error = super::read("a,b\n1,x\n", "TABLE", 0, &config).err().unwrap();
+        assert_eq!(error.line, 2);
+        assert_eq!(error.message, "failed to parse `x` as a number");
+        let error = super::read("# price\na\n", "TABLE", 0, &config).err().unwrap();
+        assert_eq!(error.line, 1);
+    }
+}
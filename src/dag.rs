@@ -0,0 +1,83 @@
+use std::collections::HashMap;
+
+use Graph;
+
+/// The structure of a task graph expressed in terms of task positions.
+pub struct Dag {
+    /// The predecessors of each task as pairs of task and arc positions.
+    pub predecessors: Vec<Vec<(usize, usize)>>,
+    /// The successors of each task as pairs of task and arc positions.
+    pub successors: Vec<Vec<(usize, usize)>>,
+    /// The positions of the tasks in a topological order.
+    pub order: Vec<usize>,
+}
+
+/// Analyze a task graph.
+///
+/// The function returns `None` if an arc refers to an unknown task or if the
+/// graph contains a cycle.
+pub fn new(graph: &Graph) -> Option<Dag> {
+    let count = graph.tasks.len();
+
+    let mut index = HashMap::new();
+    for (i, task) in graph.tasks.iter().enumerate() {
+        index.insert(task.id, i);
+    }
+
+    let mut predecessors = vec![vec![]; count];
+    let mut successors = vec![vec![]; count];
+    for (k, arc) in graph.arcs.iter().enumerate() {
+        let (from, to) = match (index.get(&arc.from), index.get(&arc.to)) {
+            (Some(&from), Some(&to)) => (from, to),
+            _ => return None,
+        };
+        predecessors[to].push((from, k));
+        successors[from].push((to, k));
+    }
+
+    let mut degrees = predecessors.iter().map(|arcs| arcs.len()).collect::<Vec<_>>();
+    let mut order = (0..count).filter(|&i| degrees[i] == 0).collect::<Vec<_>>();
+    let mut i = 0;
+    while i < order.len() {
+        let current = order[i];
+        for &(j, _) in successors[current].iter() {
+            degrees[j] -= 1;
+            if degrees[j] == 0 {
+                order.push(j);
+            }
+        }
+        i += 1;
+    }
+    if order.len() != count {
+        return None;
+    }
+
+    Some(Dag {
+        predecessors: predecessors,
+        successors: successors,
+        order: order,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use content::{new_arc, new_graph, new_task};
+
+    #[test]
+    fn new() {
+        let mut graph = new_graph(String::new(), 0);
+        graph.tasks.push(new_task(2, 0));
+        graph.tasks.push(new_task(0, 0));
+        graph.tasks.push(new_task(1, 0));
+        graph.arcs.push(new_arc(0, 0, 1, 0));
+        graph.arcs.push(new_arc(1, 1, 2, 0));
+
+        let dag = super::new(&graph).unwrap();
+        assert_eq!(dag.order, vec![1, 2, 0]);
+        assert_eq!(dag.predecessors[0], vec![(2, 1)]);
+        assert_eq!(dag.successors[1], vec![(2, 0)]);
+
+        graph.arcs.push(new_arc(2, 2, 0, 0));
+        assert!(super::new(&graph).is_none());
+    }
+}
@@ -0,0 +1,491 @@
+//! Graphviz DOT descriptions of task graphs.
+//!
+//! A task is written as a node named after the task as in TGFF files, such as
+//! `t0_1`, and labeled with its name and type. An arc is written as an edge
+//! labeled with its type. Tasks with hard deadlines are drawn with a double
+//! red border and have their deadlines in their labels. Types, deadlines and
+//! periods are also written as the attributes `type`, `deadline` and
+//! `period`, which Graphviz ignores, so that the output can be read back.
+
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::fmt::Write;
+
+use {Content, Graph, Result};
+use content::{self, new_arc, new_deadline, new_graph, new_task};
+use lexer::{escape, find, natural, title, Attributes, Lexer, Token};
+use schedule::Schedule;
+
+/// A configuration of writing.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Config<'a> {
+    /// A schedule whose processing elements are used to cluster the tasks.
+    pub schedule: Option<&'a Schedule>,
+    /// The highlighting of a critical path.
+    ///
+    /// The length of a path is the sum of the durations of its tasks, which
+    /// are taken from the schedule if present and are equal to one otherwise.
+    pub critical: bool,
+}
+
+/// The names of the attributes read from a DOT description.
+#[derive(Clone, Debug)]
+pub struct Names {
+    /// The attribute of nodes with the task type.
+    pub task_type: String,
+    /// The attribute of edges with the arc type.
+    pub arc_type: String,
+    /// The attribute of nodes with the hard deadline.
+    pub deadline: String,
+    /// The attribute of the graph with the period.
+    pub period: String,
+}
+
+impl Default for Names {
+    fn default() -> Names {
+        Names {
+            task_type: String::from("type"),
+            arc_type: String::from("type"),
+            deadline: String::from("deadline"),
+            period: String::from("period"),
+        }
+    }
+}
+
+/// Write a task graph in the DOT format.
+pub fn write(graph: &Graph, config: &Config) -> String {
+    let mut output = String::new();
+    let name = |id: usize| format!("t{}_{}", graph.id, id);
+
+    writeln!(output, "digraph \"{}_{}\" {{", escape(&graph.name), graph.id).unwrap();
+    if let Some(&period) = graph.attributes.get("PERIOD") {
+        writeln!(output, "  label=\"{} {} (period {})\";", escape(&graph.name), graph.id,
+                 period).unwrap();
+        writeln!(output, "  period={};", period).unwrap();
+    }
+
+    let critical = if config.critical { critical(graph, config.schedule) } else { vec![] };
+    let on_path = |id: usize| critical.contains(&id);
+
+    let mut clusters: Vec<Vec<usize>> = vec![];
+    let mut clustered = vec![false; graph.tasks.len()];
+    if let Some(schedule) = config.schedule {
+        for (i, task) in graph.tasks.iter().enumerate() {
+            if let Some(entry) = schedule.find(task.id) {
+                if clusters.len() <= entry.element {
+                    clusters.resize(entry.element + 1, vec![]);
+                }
+                clusters[entry.element].push(i);
+                clustered[i] = true;
+            }
+        }
+    }
+
+    let node = |output: &mut String, indent: &str, i: usize| {
+        let task = &graph.tasks[i];
+        let mut label = format!("{}\\ntype {}", name(task.id), task.kind);
+        let mut attributes = format!(", type={}", task.kind);
+        for deadline in graph.deadlines.iter().filter(|deadline| deadline.on == task.id) {
+            write!(label, "\\ndeadline {}", deadline.at).unwrap();
+            write!(attributes, ", deadline={}, color=red, peripheries=2", deadline.at).unwrap();
+        }
+        if on_path(task.id) {
+            attributes.push_str(", style=filled, fillcolor=orange");
+        }
+        writeln!(output, "{}{} [label=\"{}\"{}];", indent, name(task.id), label,
+                 attributes).unwrap();
+    };
+
+    for (element, tasks) in clusters.iter().enumerate() {
+        if tasks.is_empty() {
+            continue;
+        }
+        writeln!(output, "  subgraph cluster_{} {{", element).unwrap();
+        writeln!(output, "    label=\"PE {}\";", element).unwrap();
+        for &i in tasks.iter() {
+            node(&mut output, "    ", i);
+        }
+        writeln!(output, "  }}").unwrap();
+    }
+    for i in (0..graph.tasks.len()).filter(|&i| !clustered[i]) {
+        node(&mut output, "  ", i);
+    }
+
+    for arc in graph.arcs.iter() {
+        let position = (critical.iter().position(|&id| id == arc.from),
+                        critical.iter().position(|&id| id == arc.to));
+        let attributes = match position {
+            (Some(from), Some(to)) if from + 1 == to => ", color=orange, penwidth=2",
+            _ => "",
+        };
+        writeln!(output, "  {} -> {} [label=\"{}\", type={}{}];", name(arc.from), name(arc.to),
+                 arc.kind, arc.kind, attributes).unwrap();
+    }
+
+    writeln!(output, "}}").unwrap();
+    output
+}
+
+/// Read a task graph from a DOT description.
+///
+/// The supported subset consists of a single directed graph with node, edge
+/// and attribute statements, default attributes of nodes and edges, and
+/// subgraphs, whose contents are merged into the graph. Tasks are numbered in
+/// the order in which their nodes first appear, and arcs in the order of
+/// their edges. Tasks and arcs without types get type zero. If the name of
+/// the graph ends with an underscore followed by a number, as in `GRAPH_0`,
+/// the number is taken as the ID of the graph.
+pub fn read(input: &str, names: &Names) -> Result<Content> {
+    let mut reader = Reader {
+        lexer: Lexer::new(input),
+        names: names,
+        nodes: vec![],
+        index: HashMap::new(),
+        edges: vec![],
+        period: None,
+    };
+
+    if reader.lexer.peek()? == Some(&Token::Word(String::from("strict"))) {
+        reader.lexer.next()?;
+    }
+    match reader.lexer.next()? {
+        Some(Token::Word(ref word)) if word == "digraph" => {},
+        Some(Token::Word(ref word)) if word == "graph" => {
+            return Err(reader.lexer.error(String::from("expected a directed graph")));
+        },
+        _ => return Err(reader.lexer.error(String::from("expected `digraph`"))),
+    }
+    let name = match reader.lexer.peek()? {
+        Some(&Token::Symbol('{')) => None,
+        _ => Some(reader.lexer.value()?),
+    };
+    reader.lexer.expect('{')?;
+    reader.statements(Defaults::default())?;
+    if reader.lexer.next()?.is_some() {
+        return Err(reader.lexer.error(String::from("found an unexpected statement")));
+    }
+
+    let (name, id) = title(name);
+    let mut graph = new_graph(name, id);
+    for (i, node) in reader.nodes.iter().enumerate() {
+        graph.tasks.push(new_task(i, node.kind));
+        if let Some(at) = node.deadline {
+            let id = graph.deadlines.len();
+            graph.deadlines.push(new_deadline(id, i, at));
+        }
+    }
+    for (k, &(from, to, kind)) in reader.edges.iter().enumerate() {
+        graph.arcs.push(new_arc(k, from, to, kind));
+    }
+
+    let mut content = content::new();
+    if let Some(period) = reader.period {
+        graph.attributes.insert(String::from("PERIOD"), period);
+        content.attributes.insert(String::from("HYPERPERIOD"), period);
+    }
+    content.graphs.push(graph);
+
+    Ok(content)
+}
+
+/// Find the IDs of the tasks on a longest path.
+fn critical(graph: &Graph, schedule: Option<&Schedule>) -> Vec<usize> {
+    let dag = match ::dag::new(graph) {
+        Some(dag) => dag,
+        _ => return vec![],
+    };
+    let duration = |i: usize| {
+        let entry = schedule.and_then(|schedule| schedule.find(graph.tasks[i].id));
+        entry.map(|entry| entry.finish - entry.start).unwrap_or(1.0)
+    };
+
+    let count = graph.tasks.len();
+    let mut length = vec![0.0; count];
+    let mut previous = vec![None; count];
+    for &i in dag.order.iter() {
+        length[i] += duration(i);
+        for &(j, _) in dag.successors[i].iter() {
+            if length[i] > length[j] {
+                length[j] = length[i];
+                previous[j] = Some(i);
+            }
+        }
+    }
+
+    let last = (0..count).max_by(|&i, &j| {
+        length[i].partial_cmp(&length[j]).unwrap_or(Ordering::Equal)
+    });
+    let mut last = match last {
+        Some(last) => last,
+        _ => return vec![],
+    };
+    let mut path = vec![graph.tasks[last].id];
+    while let Some(i) = previous[last] {
+        path.push(graph.tasks[i].id);
+        last = i;
+    }
+    path.reverse();
+    path
+}
+
+struct Reader<'a> {
+    lexer: Lexer<'a>,
+    names: &'a Names,
+    nodes: Vec<Node>,
+    index: HashMap<String, usize>,
+    edges: Vec<(usize, usize, usize)>,
+    period: Option<usize>,
+}
+
+struct Node {
+    kind: usize,
+    deadline: Option<usize>,
+}
+
+#[derive(Clone, Default)]
+struct Defaults {
+    task_type: Option<usize>,
+    deadline: Option<usize>,
+    arc_type: Option<usize>,
+}
+
+impl<'a> Reader<'a> {
+    fn statements(&mut self, mut defaults: Defaults) -> Result<()> {
+        loop {
+            let token = match self.lexer.next()? {
+                Some(token) => token,
+                _ => return Err(self.lexer.error(String::from("expected `}`"))),
+            };
+            let name = match token {
+                Token::Symbol('}') => return Ok(()),
+                Token::Symbol(';') | Token::Symbol(',') => continue,
+                Token::Symbol('{') => {
+                    self.statements(defaults.clone())?;
+                    continue;
+                },
+                Token::Word(ref word) if word == "subgraph" => {
+                    if self.lexer.peek()? != Some(&Token::Symbol('{')) {
+                        self.lexer.value()?;
+                    }
+                    self.lexer.expect('{')?;
+                    self.statements(defaults.clone())?;
+                    continue;
+                },
+                Token::Word(ref word) if self.lexer.peek()? == Some(&Token::Symbol('[')) &&
+                                         (word == "graph" || word == "node" ||
+                                          word == "edge") => {
+                    let attributes = self.attributes()?;
+                    match &word[..] {
+                        "graph" => self.graph(&attributes)?,
+                        "node" => {
+                            let mut node = Node { kind: 0, deadline: None };
+                            update(&mut node, &attributes, self.names)?;
+                            if find(&attributes, &self.names.task_type).is_some() {
+                                defaults.task_type = Some(node.kind);
+                            }
+                            if node.deadline.is_some() {
+                                defaults.deadline = node.deadline;
+                            }
+                        },
+                        _ => defaults.arc_type = Some(self.edge(&attributes, 0)?),
+                    }
+                    continue;
+                },
+                Token::Word(word) | Token::Text(word) => word,
+                _ => return Err(self.lexer.error(String::from("found an unexpected token"))),
+            };
+
+            if self.lexer.accept('=')? {
+                let value = self.lexer.value()?;
+                let line = self.lexer.line;
+                self.graph(&vec![(name, value, line)])?;
+                continue;
+            }
+
+            let mut chain = vec![name];
+            self.port()?;
+            while let Some(&Token::Arrow(arrow)) = self.lexer.peek()? {
+                self.lexer.next()?;
+                if arrow != "->" {
+                    return Err(self.lexer.error(String::from("expected a directed edge")));
+                }
+                chain.push(self.lexer.value()?);
+                self.port()?;
+            }
+            let attributes = self.attributes()?;
+
+            let tasks = chain.into_iter().map(|name| {
+                let count = self.nodes.len();
+                let task = *self.index.entry(name).or_insert(count);
+                if task == count {
+                    self.nodes.push(Node {
+                        kind: defaults.task_type.unwrap_or(0),
+                        deadline: defaults.deadline,
+                    });
+                }
+                task
+            }).collect::<Vec<_>>();
+            if tasks.len() == 1 {
+                update(&mut self.nodes[tasks[0]], &attributes, self.names)?;
+            } else {
+                let kind = self.edge(&attributes, defaults.arc_type.unwrap_or(0))?;
+                for pair in tasks.windows(2) {
+                    self.edges.push((pair[0], pair[1], kind));
+                }
+            }
+        }
+    }
+
+    fn attributes(&mut self) -> Result<Attributes> {
+        let mut attributes = vec![];
+        while self.lexer.accept('[')? {
+            loop {
+                if self.lexer.accept(']')? {
+                    break;
+                }
+                if self.lexer.accept(',')? || self.lexer.accept(';')? {
+                    continue;
+                }
+                let name = self.lexer.value()?;
+                let line = self.lexer.line;
+                self.lexer.expect('=')?;
+                attributes.push((name, self.lexer.value()?, line));
+            }
+        }
+        Ok(attributes)
+    }
+
+    fn port(&mut self) -> Result<()> {
+        while self.lexer.accept(':')? {
+            self.lexer.value()?;
+        }
+        Ok(())
+    }
+
+    fn graph(&mut self, attributes: &Attributes) -> Result<()> {
+        if let Some(&(_, ref value, line)) = find(attributes, &self.names.period) {
+            self.period = Some(natural(value, &self.names.period, line)?);
+        }
+        Ok(())
+    }
+
+    fn edge(&self, attributes: &Attributes, kind: usize) -> Result<usize> {
+        match find(attributes, &self.names.arc_type) {
+            Some(&(_, ref value, line)) => natural(value, &self.names.arc_type, line),
+            _ => Ok(kind),
+        }
+    }
+}
+
+fn update(node: &mut Node, attributes: &Attributes, names: &Names) -> Result<()> {
+    if let Some(&(_, ref value, line)) = find(attributes, &names.task_type) {
+        node.kind = natural(value, &names.task_type, line)?;
+    }
+    if let Some(&(_, ref value, line)) = find(attributes, &names.deadline) {
+        node.deadline = Some(natural(value, &names.deadline, line)?);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use schedule::{Entry, Schedule};
+    use super::{Config, Names};
+
+    const INPUT: &str = "@GRAPH 0 {
+    PERIOD 10
+    TASK t0_0 TYPE 3
+    TASK t0_1 TYPE 4
+    TASK t0_2 TYPE 5
+    TASK t0_3 TYPE 6
+    ARC a0_0 FROM t0_0 TO t0_1 TYPE 0
+    ARC a0_1 FROM t0_0 TO t0_2 TYPE 1
+    ARC a0_2 FROM t0_1 TO t0_3 TYPE 2
+    ARC a0_3 FROM t0_2 TO t0_3 TYPE 3
+    HARD_DEADLINE d0_0 ON t0_3 AT 9
+}
+";
+
+    #[test]
+    fn write() {
+        let content = ::parse(INPUT).unwrap();
+        let graph = &content.graphs[0];
+
+        let output = super::write(graph, &Config::default());
+        assert_eq!(output, "digraph \"GRAPH_0\" {
+  label=\"GRAPH 0 (period 10)\";
+  period=10;
+  t0_0 [label=\"t0_0\\ntype 3\", type=3];
+  t0_1 [label=\"t0_1\\ntype 4\", type=4];
+  t0_2 [label=\"t0_2\\ntype 5\", type=5];
+  t0_3 [label=\"t0_3\\ntype 6\\ndeadline 9\", type=6, deadline=9, color=red, peripheries=2];
+  t0_0 -> t0_1 [label=\"0\", type=0];
+  t0_0 -> t0_2 [label=\"1\", type=1];
+  t0_1 -> t0_3 [label=\"2\", type=2];
+  t0_2 -> t0_3 [label=\"3\", type=3];
+}
+");
+
+        let schedule = Schedule { entries: vec![
+            Entry { task: 0, element: 0, version: 0, start: 0.0, finish: 1.0 },
+            Entry { task: 1, element: 0, version: 0, start: 1.0, finish: 2.0 },
+            Entry { task: 2, element: 1, version: 0, start: 2.0, finish: 5.0 },
+            Entry { task: 3, element: 0, version: 0, start: 5.0, finish: 6.0 },
+        ]};
+        let config = Config { schedule: Some(&schedule), critical: true };
+        let output = super::write(graph, &config);
+        assert!(output.contains("  subgraph cluster_0 {
+    label=\"PE 0\";
+    t0_0 [label=\"t0_0\\ntype 3\", type=3, style=filled, fillcolor=orange];
+    t0_1 [label=\"t0_1\\ntype 4\", type=4];
+    t0_3 [label=\"t0_3\\ntype 6\\ndeadline 9\", type=6, deadline=9, color=red, \
+peripheries=2, style=filled, fillcolor=orange];
+  }
+  subgraph cluster_1 {
+    label=\"PE 1\";
+    t0_2 [label=\"t0_2\\ntype 5\", type=5, style=filled, fillcolor=orange];
+  }
+"));
+        assert!(output.contains("  t0_0 -> t0_2 [label=\"1\", type=1, color=orange, \
+penwidth=2];\n"));
+        assert!(output.contains("  t0_0 -> t0_1 [label=\"0\", type=0];\n"));
+    }
+
+    #[test]
+    fn read() {
+        let content = ::parse(INPUT).unwrap();
+        let output = super::write(&content.graphs[0], &Config::default());
+        let result = super::read(&output, &Names::default()).unwrap();
+        assert_eq!(result.attributes["HYPERPERIOD"], 10);
+        let graph = &result.graphs[0];
+        assert_eq!((&graph.name[..], graph.id), ("GRAPH", 0));
+        assert_eq!(graph.attributes["PERIOD"], 10);
+        assert_eq!(graph.tasks.iter().map(|task| task.kind).collect::<Vec<_>>(), &[3, 4, 5, 6]);
+        assert_eq!(graph.arcs.iter().map(|arc| (arc.from, arc.to, arc.kind)).collect::<Vec<_>>(),
+                   &[(0, 1, 0), (0, 2, 1), (1, 3, 2), (2, 3, 3)]);
+        assert_eq!(graph.deadlines.len(), 1);
+        assert_eq!((graph.deadlines[0].on, graph.deadlines[0].at), (3, 9));
+
+        let names = Names { task_type: String::from("kind"), arc_type: String::from("weight"),
+                            ..Names::default() };
+        let result = super::read("strict digraph {
+  node [kind=2];
+  a; b [kind=5, deadline=\"7\"]
+  subgraph cluster { edge [weight=1]; a -> b -> c }
+  c -> a [weight=4];
+}
+", &names).unwrap();
+        let graph = &result.graphs[0];
+        assert_eq!((&graph.name[..], graph.id), ("TASK_GRAPH", 0));
+        assert_eq!(graph.tasks.iter().map(|task| task.kind).collect::<Vec<_>>(), &[2, 5, 2]);
+        assert_eq!(graph.arcs.iter().map(|arc| (arc.from, arc.to, arc.kind)).collect::<Vec<_>>(),
+                   &[(0, 1, 1), (1, 2, 1), (2, 0, 4)]);
+        assert_eq!((graph.deadlines[0].on, graph.deadlines[0].at), (1, 7));
+
+        let error = super::read("graph { a -- b }", &names).err().unwrap();
+        assert_eq!(error.line, 1);
+        let error = super::read("digraph {\n  a -> b\n  b [kind=x]\n}", &names).err().unwrap();
+        assert_eq!(error.line, 3);
+        assert_eq!(error.message, "failed to parse the value `x` of `kind`");
+    }
+}
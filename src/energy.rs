@@ -0,0 +1,217 @@
+//! Energy consumption of schedules.
+//!
+//! The energy of a task is its dynamic power, which is read from the table of
+//! the processing element executing the task, multiplied by its duration.
+//! Processing elements can also dissipate static power, which is read from an
+//! attribute of their tables and is consumed both when they are busy and when
+//! they are idle.
+
+use std::collections::HashMap;
+
+use Graph;
+use communication::Communication;
+use platform::Platform;
+use schedule::Schedule;
+
+/// A configuration of energy estimation.
+#[derive(Clone, Debug)]
+pub struct Config {
+    /// The name of the column with dynamic power.
+    pub dynamic_power: String,
+    /// The name of the attribute with static power, if any.
+    pub static_power: Option<String>,
+    /// The energy consumed per unit of data transferred between two distinct
+    /// processing elements.
+    pub transfer_energy: f64,
+}
+
+/// The energy consumed by a schedule over a hyperperiod.
+#[derive(Clone, Debug)]
+pub struct Energy {
+    /// The energy of each task per instance of the graph in the order of the
+    /// entries of the schedule.
+    pub tasks: Vec<f64>,
+    /// The energy of each processing element.
+    pub elements: Vec<Consumption>,
+    /// The energy of communication.
+    pub communication: f64,
+}
+
+/// The energy consumed by a processing element.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Consumption {
+    /// The energy consumed while executing tasks.
+    pub busy: f64,
+    /// The energy consumed while waiting.
+    pub idle: f64,
+}
+
+impl Default for Config {
+    fn default() -> Config {
+        Config {
+            dynamic_power: String::from("dynamic_power"),
+            static_power: None,
+            transfer_energy: 0.0,
+        }
+    }
+}
+
+impl Energy {
+    /// Return the total energy.
+    pub fn total(&self) -> f64 {
+        self.elements.iter().fold(self.communication, |sum, consumption| {
+            sum + consumption.busy + consumption.idle
+        })
+    }
+}
+
+/// Estimate the energy consumed by a schedule.
+///
+/// The schedule is assumed to be repeated every `PERIOD` of the graph within
+/// the hyperperiod, and the graph is executed once if it has no period. The
+/// number of repetitions is that of the periods starting within the
+/// hyperperiod, which is the ratio of the two rounded up. The function returns
+/// `None` if the dynamic power of a scheduled task is unknown.
+pub fn estimate(schedule: &Schedule, graph: &Graph, platform: &Platform,
+                communication: &Communication, hyperperiod: f64, config: &Config)
+                -> Option<Energy> {
+
+    let repetitions = instances(graph, hyperperiod) as f64;
+
+    let mut kinds = HashMap::new();
+    for task in graph.tasks.iter() {
+        kinds.insert(task.id, task.kind);
+    }
+
+    let mut tasks = Vec::with_capacity(schedule.entries.len());
+    let mut busy = vec![0.0; platform.len()];
+    let mut elements = vec![Consumption::default(); platform.len()];
+    for entry in schedule.entries.iter() {
+        let power = match (kinds.get(&entry.task), platform.elements.get(entry.element)) {
+            (Some(&kind), Some(element)) => {
+                element.value(&config.dynamic_power, kind, entry.version)
+            },
+            _ => None,
+        };
+        let energy = match power {
+            Some(power) => power * (entry.finish - entry.start),
+            _ => return None,
+        };
+        tasks.push(energy);
+        busy[entry.element] += entry.finish - entry.start;
+        elements[entry.element].busy += repetitions * energy;
+    }
+
+    if let Some(ref name) = config.static_power {
+        for (i, element) in platform.elements.iter().enumerate() {
+            if let Some(power) = element.attribute(name) {
+                let time = repetitions * busy[i];
+                elements[i].busy += power * time;
+                elements[i].idle += power * (hyperperiod - time).max(0.0);
+            }
+        }
+    }
+
+    let mut transfer = 0.0;
+    for arc in graph.arcs.iter() {
+        if let (Some(from), Some(to)) = (schedule.find(arc.from), schedule.find(arc.to)) {
+            if from.element != to.element {
+                transfer += communication.volume(arc.kind) * config.transfer_energy;
+            }
+        }
+    }
+
+    Some(Energy {
+        tasks: tasks,
+        elements: elements,
+        communication: repetitions * transfer,
+    })
+}
+
+/// Return the number of instances of a graph released within a hyperperiod.
+pub(crate) fn instances(graph: &Graph, hyperperiod: f64) -> usize {
+    match graph.attributes.get("PERIOD") {
+        Some(&period) if period > 0 => {
+            (hyperperiod / period as f64 - 1e-9).ceil().max(1.0) as usize
+        },
+        _ => 1,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use communication::Communication;
+    use platform::Platform;
+    use schedule::{Entry, Schedule};
+    use super::{Config, Consumption};
+
+    const INPUT: &str = "@GRAPH 0 {
+    PERIOD 10
+    TASK t0_0 TYPE 0
+    TASK t0_1 TYPE 1
+    TASK t0_2 TYPE 1
+    ARC a0_0 FROM t0_0 TO t0_1 TYPE 0
+    ARC a0_1 FROM t0_0 TO t0_2 TYPE 0
+}
+@CORE 0 {
+# price static_power
+  1     0.5
+#----
+# type version dynamic_power execution_time
+  0    0       2             1
+  1    0       3             2
+}
+@CORE 1 {
+# price
+  1
+#----
+# type version dynamic_power execution_time
+  1    0       4             1
+}
+";
+
+    #[test]
+    fn estimate() {
+        let content = ::parse(INPUT).unwrap();
+        let platform = Platform::new(&content, "CORE");
+        let communication = Communication::new(2.0);
+        let schedule = Schedule { entries: vec![
+            Entry { task: 0, element: 0, version: 0, start: 0.0, finish: 1.0 },
+            Entry { task: 1, element: 0, version: 0, start: 1.0, finish: 3.0 },
+            Entry { task: 2, element: 1, version: 0, start: 1.5, finish: 2.5 },
+        ]};
+        let config = Config {
+            static_power: Some(String::from("static_power")),
+            transfer_energy: 0.25,
+            ..Config::default()
+        };
+
+        let energy = super::estimate(&schedule, &content.graphs[0], &platform,
+                                     &communication, 20.0, &config).unwrap();
+        assert_eq!(energy.tasks, vec![2.0, 6.0, 4.0]);
+        assert_eq!(energy.elements, vec![
+            Consumption { busy: 16.0 + 3.0, idle: 7.0 },
+            Consumption { busy: 8.0, idle: 0.0 },
+        ]);
+        assert_eq!(energy.communication, 0.5);
+        assert_eq!(energy.total(), 34.5);
+
+        let energy = super::estimate(&schedule, &content.graphs[0], &platform,
+                                     &communication, 25.0, &config).unwrap();
+        assert_eq!(energy.elements, vec![
+            Consumption { busy: 24.0 + 4.5, idle: 8.0 },
+            Consumption { busy: 12.0, idle: 0.0 },
+        ]);
+
+        let mut graph = content.graphs[0].clone();
+        assert_eq!(super::instances(&graph, 20.0), 2);
+        assert_eq!(super::instances(&graph, 25.0), 3);
+        assert_eq!(super::instances(&graph, 5.0), 1);
+        graph.attributes.clear();
+        assert_eq!(super::instances(&graph, 25.0), 1);
+
+        let config = Config { dynamic_power: String::from("power"), ..Config::default() };
+        assert!(super::estimate(&schedule, &content.graphs[0], &platform,
+                                &communication, 20.0, &config).is_none());
+    }
+}
@@ -0,0 +1,825 @@
+//! A serde data format for TGFF files.
+
+use std::collections::HashMap;
+use std::fmt;
+
+use serde::de::value::{MapAccessDeserializer, MapDeserializer, SeqDeserializer};
+use serde::de::{self, DeserializeOwned, IntoDeserializer, Visitor};
+use serde::ser::{self, Serialize};
+
+use {Content, Error, Graph, Result, Table};
+use content::{self, new_arc, new_column, new_deadline, new_graph, new_table, new_task};
+
+/// Deserialize a value from a string containing a TGFF file.
+///
+/// The file is presented as a map with the following keys:
+///
+/// * `attributes` is a map from the names of global attributes, such as
+///   `HYPERPERIOD`, to natural numbers;
+/// * `graphs` is a sequence of maps with the keys `name`, `id`, `attributes`,
+///   `tasks`, `arcs` and `deadlines`, where tasks are maps with the keys `id`
+///   and `kind`, arcs with the keys `id`, `from`, `to` and `kind`, and
+///   deadlines with the keys `id`, `on` and `at`; and
+/// * `tables` is a sequence of maps with the keys `name`, `id`, `attributes`,
+///   `columns` and `rows`, where columns are maps with the keys `name` and
+///   `data`, and rows are maps from the names of the columns to the values.
+///
+/// This is the shape of `Content`, and a structure describing a table can
+/// choose between columns and rows. Values of tables are real numbers, which
+/// can also be read as integers if they are integral. Errors that are not
+/// tied to a line of the input are reported on line zero.
+pub fn from_str<T: DeserializeOwned>(input: &str) -> Result<T> {
+    T::deserialize(convert(::parse(input)?)?)
+}
+
+/// Serialize a value into a string in the TGFF format.
+///
+/// The value should have the shape described in `from_str`. Missing IDs are
+/// filled in with positions, missing attributes and sequences are considered
+/// empty, and a table is written either from its columns or from its rows,
+/// whose columns are ordered as in the first row. The columns of a table
+/// should have the same length. Errors are reported on line zero.
+pub fn to_string<T: Serialize + ?Sized>(value: &T) -> Result<String> {
+    Ok(build(value.serialize(Builder)?)?.to_string())
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Value {
+    Empty,
+    Natural(u64),
+    Real(f64),
+    Text(String),
+    List(Vec<Value>),
+    Map(Vec<(String, Value)>),
+}
+
+struct Builder;
+
+struct SeqBuilder {
+    variant: Option<&'static str>,
+    items: Vec<Value>,
+}
+
+struct MapBuilder {
+    variant: Option<&'static str>,
+    entries: Vec<(String, Value)>,
+    key: Option<String>,
+}
+
+impl de::Error for Error {
+    fn custom<T: fmt::Display>(message: T) -> Error {
+        Error { line: 0, message: message.to_string() }
+    }
+}
+
+impl ser::Error for Error {
+    fn custom<T: fmt::Display>(message: T) -> Error {
+        Error { line: 0, message: message.to_string() }
+    }
+}
+
+macro_rules! integer(
+    ($($method:ident),*) => ($(
+        fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+            match self {
+                Value::Real(value) if value.fract() == 0.0 && value >= 0.0 => {
+                    visitor.visit_u64(value as u64)
+                },
+                Value::Real(value) if value.fract() == 0.0 => visitor.visit_i64(value as i64),
+                value => value.deserialize_any(visitor),
+            }
+        }
+    )*);
+);
+
+impl<'de> de::Deserializer<'de> for Value {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        match self {
+            Value::Empty => visitor.visit_unit(),
+            Value::Natural(value) => visitor.visit_u64(value),
+            Value::Real(value) => visitor.visit_f64(value),
+            Value::Text(value) => visitor.visit_string(value),
+            Value::List(items) => {
+                let mut items = SeqDeserializer::new(items.into_iter());
+                let value = visitor.visit_seq(&mut items)?;
+                items.end()?;
+                Ok(value)
+            },
+            Value::Map(entries) => {
+                let mut entries = MapDeserializer::new(entries.into_iter());
+                let value = visitor.visit_map(&mut entries)?;
+                entries.end()?;
+                Ok(value)
+            },
+        }
+    }
+
+    integer!(deserialize_u8, deserialize_u16, deserialize_u32, deserialize_u64,
+             deserialize_i8, deserialize_i16, deserialize_i32, deserialize_i64);
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        match self {
+            Value::Empty => visitor.visit_none(),
+            value => visitor.visit_some(value),
+        }
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(self, _: &'static str, visitor: V)
+                                                   -> Result<V::Value> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(self, _: &'static str, _: &'static [&'static str],
+                                         visitor: V) -> Result<V::Value> {
+        match self {
+            Value::Text(value) => visitor.visit_enum(value.into_deserializer()),
+            Value::Map(entries) => {
+                let entries = MapDeserializer::new(entries.into_iter());
+                visitor.visit_enum(MapAccessDeserializer::new(entries))
+            },
+            _ => raise!(0, "expected an enum"),
+        }
+    }
+
+    forward_to_deserialize_any! {
+        bool f32 f64 char str string bytes byte_buf unit unit_struct seq tuple tuple_struct map
+        struct identifier ignored_any
+    }
+}
+
+impl<'de> IntoDeserializer<'de, Error> for Value {
+    type Deserializer = Value;
+
+    #[inline]
+    fn into_deserializer(self) -> Value {
+        self
+    }
+}
+
+impl ser::Serializer for Builder {
+    type Ok = Value;
+    type Error = Error;
+    type SerializeSeq = SeqBuilder;
+    type SerializeTuple = SeqBuilder;
+    type SerializeTupleStruct = SeqBuilder;
+    type SerializeTupleVariant = SeqBuilder;
+    type SerializeMap = MapBuilder;
+    type SerializeStruct = MapBuilder;
+    type SerializeStructVariant = MapBuilder;
+
+    fn serialize_bool(self, _: bool) -> Result<Value> {
+        raise!(0, "found a boolean, which is not supported")
+    }
+
+    fn serialize_i8(self, value: i8) -> Result<Value> {
+        self.serialize_i64(value as i64)
+    }
+
+    fn serialize_i16(self, value: i16) -> Result<Value> {
+        self.serialize_i64(value as i64)
+    }
+
+    fn serialize_i32(self, value: i32) -> Result<Value> {
+        self.serialize_i64(value as i64)
+    }
+
+    fn serialize_i64(self, value: i64) -> Result<Value> {
+        if value < 0 {
+            Ok(Value::Real(value as f64))
+        } else {
+            Ok(Value::Natural(value as u64))
+        }
+    }
+
+    fn serialize_u8(self, value: u8) -> Result<Value> {
+        Ok(Value::Natural(value as u64))
+    }
+
+    fn serialize_u16(self, value: u16) -> Result<Value> {
+        Ok(Value::Natural(value as u64))
+    }
+
+    fn serialize_u32(self, value: u32) -> Result<Value> {
+        Ok(Value::Natural(value as u64))
+    }
+
+    fn serialize_u64(self, value: u64) -> Result<Value> {
+        Ok(Value::Natural(value))
+    }
+
+    fn serialize_f32(self, value: f32) -> Result<Value> {
+        Ok(Value::Real(value as f64))
+    }
+
+    fn serialize_f64(self, value: f64) -> Result<Value> {
+        Ok(Value::Real(value))
+    }
+
+    fn serialize_char(self, value: char) -> Result<Value> {
+        Ok(Value::Text(value.to_string()))
+    }
+
+    fn serialize_str(self, value: &str) -> Result<Value> {
+        Ok(Value::Text(value.to_string()))
+    }
+
+    fn serialize_bytes(self, _: &[u8]) -> Result<Value> {
+        raise!(0, "found bytes, which are not supported")
+    }
+
+    fn serialize_none(self) -> Result<Value> {
+        Ok(Value::Empty)
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<Value> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<Value> {
+        Ok(Value::Empty)
+    }
+
+    fn serialize_unit_struct(self, _: &'static str) -> Result<Value> {
+        Ok(Value::Empty)
+    }
+
+    fn serialize_unit_variant(self, _: &'static str, _: u32, variant: &'static str)
+                              -> Result<Value> {
+        Ok(Value::Text(variant.to_string()))
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(self, _: &'static str, value: &T)
+                                                       -> Result<Value> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(self, _: &'static str, _: u32,
+                                                        variant: &'static str, value: &T)
+                                                        -> Result<Value> {
+        Ok(Value::Map(vec![(variant.to_string(), value.serialize(self)?)]))
+    }
+
+    fn serialize_seq(self, length: Option<usize>) -> Result<SeqBuilder> {
+        Ok(SeqBuilder { variant: None, items: Vec::with_capacity(length.unwrap_or(0)) })
+    }
+
+    fn serialize_tuple(self, length: usize) -> Result<SeqBuilder> {
+        self.serialize_seq(Some(length))
+    }
+
+    fn serialize_tuple_struct(self, _: &'static str, length: usize) -> Result<SeqBuilder> {
+        self.serialize_seq(Some(length))
+    }
+
+    fn serialize_tuple_variant(self, _: &'static str, _: u32, variant: &'static str,
+                               length: usize) -> Result<SeqBuilder> {
+        Ok(SeqBuilder { variant: Some(variant), items: Vec::with_capacity(length) })
+    }
+
+    fn serialize_map(self, length: Option<usize>) -> Result<MapBuilder> {
+        Ok(MapBuilder {
+            variant: None,
+            entries: Vec::with_capacity(length.unwrap_or(0)),
+            key: None,
+        })
+    }
+
+    fn serialize_struct(self, _: &'static str, length: usize) -> Result<MapBuilder> {
+        self.serialize_map(Some(length))
+    }
+
+    fn serialize_struct_variant(self, _: &'static str, _: u32, variant: &'static str,
+                                length: usize) -> Result<MapBuilder> {
+        Ok(MapBuilder {
+            variant: Some(variant),
+            entries: Vec::with_capacity(length),
+            key: None,
+        })
+    }
+}
+
+impl SeqBuilder {
+    fn push<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<()> {
+        self.items.push(value.serialize(Builder)?);
+        Ok(())
+    }
+
+    fn finish(self) -> Result<Value> {
+        let value = Value::List(self.items);
+        Ok(match self.variant {
+            Some(variant) => Value::Map(vec![(variant.to_string(), value)]),
+            _ => value,
+        })
+    }
+}
+
+impl ser::SerializeSeq for SeqBuilder {
+    type Ok = Value;
+    type Error = Error;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<()> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Value> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeTuple for SeqBuilder {
+    type Ok = Value;
+    type Error = Error;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<()> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Value> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeTupleStruct for SeqBuilder {
+    type Ok = Value;
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<()> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Value> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeTupleVariant for SeqBuilder {
+    type Ok = Value;
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<()> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Value> {
+        self.finish()
+    }
+}
+
+impl MapBuilder {
+    fn insert<T: Serialize + ?Sized>(&mut self, key: String, value: &T) -> Result<()> {
+        match value.serialize(Builder)? {
+            Value::Empty => {},
+            value => self.entries.push((key, value)),
+        }
+        Ok(())
+    }
+
+    fn finish(self) -> Result<Value> {
+        let value = Value::Map(self.entries);
+        Ok(match self.variant {
+            Some(variant) => Value::Map(vec![(variant.to_string(), value)]),
+            _ => value,
+        })
+    }
+}
+
+impl ser::SerializeMap for MapBuilder {
+    type Ok = Value;
+    type Error = Error;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<()> {
+        self.key = Some(match key.serialize(Builder)? {
+            Value::Text(key) => key,
+            Value::Natural(key) => key.to_string(),
+            _ => raise!(0, "expected a string or a natural number as a key"),
+        });
+        Ok(())
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<()> {
+        match self.key.take() {
+            Some(key) => self.insert(key, value),
+            _ => raise!(0, "found a value without a key"),
+        }
+    }
+
+    fn end(self) -> Result<Value> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeStruct for MapBuilder {
+    type Ok = Value;
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, key: &'static str, value: &T)
+                                              -> Result<()> {
+        self.insert(key.to_string(), value)
+    }
+
+    fn end(self) -> Result<Value> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeStructVariant for MapBuilder {
+    type Ok = Value;
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, key: &'static str, value: &T)
+                                              -> Result<()> {
+        self.insert(key.to_string(), value)
+    }
+
+    fn end(self) -> Result<Value> {
+        self.finish()
+    }
+}
+
+fn convert(content: Content) -> Result<Value> {
+    let natural = |value: usize| Value::Natural(value as u64);
+    let record = |entries: &[(&str, usize)]| {
+        Value::Map(entries.iter().map(|&(key, value)| {
+            (key.to_string(), Value::Natural(value as u64))
+        }).collect())
+    };
+
+    let graphs = content.graphs.into_iter().map(|graph| {
+        Value::Map(vec![
+            (String::from("name"), Value::Text(graph.name)),
+            (String::from("id"), Value::Natural(graph.id as u64)),
+            (String::from("attributes"), attributes(graph.attributes, natural)),
+            (String::from("tasks"), Value::List(graph.tasks.iter().map(|task| {
+                record(&[("id", task.id), ("kind", task.kind)])
+            }).collect())),
+            (String::from("arcs"), Value::List(graph.arcs.iter().map(|arc| {
+                record(&[("id", arc.id), ("from", arc.from), ("to", arc.to), ("kind", arc.kind)])
+            }).collect())),
+            (String::from("deadlines"), Value::List(graph.deadlines.iter().map(|deadline| {
+                record(&[("id", deadline.id), ("on", deadline.on), ("at", deadline.at)])
+            }).collect())),
+        ])
+    }).collect();
+
+    let mut tables = vec![];
+    for table in content.tables {
+        let count = match content::rows(&table) {
+            Some(count) => count,
+            _ => raise!(0, "found columns of different lengths in the table `{}`", table.name),
+        };
+        let rows = (0..count).map(|i| {
+            Value::Map(table.columns.iter().map(|column| {
+                (column.name.clone(), Value::Real(column.data[i]))
+            }).collect())
+        }).collect();
+        tables.push(Value::Map(vec![
+            (String::from("name"), Value::Text(table.name)),
+            (String::from("id"), Value::Natural(table.id as u64)),
+            (String::from("attributes"), attributes(table.attributes, Value::Real)),
+            (String::from("columns"), Value::List(table.columns.into_iter().map(|column| {
+                Value::Map(vec![
+                    (String::from("name"), Value::Text(column.name)),
+                    (String::from("data"), Value::List(column.data.into_iter().map(Value::Real)
+                                                                            .collect())),
+                ])
+            }).collect())),
+            (String::from("rows"), Value::List(rows)),
+        ]));
+    }
+
+    Ok(Value::Map(vec![
+        (String::from("attributes"), attributes(content.attributes, natural)),
+        (String::from("graphs"), Value::List(graphs)),
+        (String::from("tables"), Value::List(tables)),
+    ]))
+}
+
+fn attributes<T, F>(attributes: HashMap<String, T>, convert: F) -> Value
+    where F: Fn(T) -> Value
+{
+    let mut entries = attributes.into_iter().map(|(name, value)| (name, convert(value)))
+                                            .collect::<Vec<_>>();
+    entries.sort_by(|one, other| one.0.cmp(&other.0));
+    Value::Map(entries)
+}
+
+fn build(value: Value) -> Result<Content> {
+    let mut content = content::new();
+    for (key, value) in fields(value, "the content")? {
+        match &key[..] {
+            "attributes" => for (name, value) in fields(value, &key)? {
+                let value = natural(value, &name)?;
+                content.attributes.insert(token(name)?, value);
+            },
+            "graphs" => for (i, value) in items(value, &key)?.into_iter().enumerate() {
+                content.graphs.push(graph(value, i)?);
+            },
+            "tables" => for (i, value) in items(value, &key)?.into_iter().enumerate() {
+                content.tables.push(table(value, i)?);
+            },
+            _ => raise!(0, "found an unknown field `{}`", key),
+        }
+    }
+    Ok(content)
+}
+
+fn graph(value: Value, position: usize) -> Result<Graph> {
+    let mut graph = new_graph(String::new(), position);
+    for (key, value) in fields(value, "a graph")? {
+        match &key[..] {
+            "name" => graph.name = token(text(value, &key)?)?,
+            "id" => graph.id = natural(value, &key)?,
+            "attributes" => for (name, value) in fields(value, &key)? {
+                let value = natural(value, &name)?;
+                graph.attributes.insert(token(name)?, value);
+            },
+            "tasks" => for (i, value) in items(value, &key)?.into_iter().enumerate() {
+                let fields = record(value, &["id", "kind"])?;
+                graph.tasks.push(new_task(fields[0].unwrap_or(i), required(fields[1], "kind")?));
+            },
+            "arcs" => for (i, value) in items(value, &key)?.into_iter().enumerate() {
+                let fields = record(value, &["id", "from", "to", "kind"])?;
+                graph.arcs.push(new_arc(fields[0].unwrap_or(i), required(fields[1], "from")?,
+                                        required(fields[2], "to")?, required(fields[3], "kind")?));
+            },
+            "deadlines" => for (i, value) in items(value, &key)?.into_iter().enumerate() {
+                let fields = record(value, &["id", "on", "at"])?;
+                graph.deadlines.push(new_deadline(fields[0].unwrap_or(i),
+                                                  required(fields[1], "on")?,
+                                                  required(fields[2], "at")?));
+            },
+            _ => raise!(0, "found an unknown field `{}`", key),
+        }
+    }
+    if graph.name.is_empty() {
+        raise!(0, "missing the field `name` of a graph");
+    }
+    Ok(graph)
+}
+
+fn table(value: Value, position: usize) -> Result<Table> {
+    let mut table = new_table(String::new(), position);
+    for (key, value) in fields(value, "a table")? {
+        match &key[..] {
+            "name" => table.name = token(text(value, &key)?)?,
+            "id" => table.id = natural(value, &key)?,
+            "attributes" => for (name, value) in fields(value, &key)? {
+                let value = real(value, &name)?;
+                table.attributes.insert(token(name)?, value);
+            },
+            "columns" | "rows" if !table.columns.is_empty() => {
+                raise!(0, "found both columns and rows in a table");
+            },
+            "columns" => for value in items(value, &key)? {
+                let mut column = new_column(String::new());
+                for (key, value) in fields(value, "a column")? {
+                    match &key[..] {
+                        "name" => column.name = token(text(value, &key)?)?,
+                        "data" => for value in items(value, &key)? {
+                            column.data.push(real(value, &key)?);
+                        },
+                        _ => raise!(0, "found an unknown field `{}`", key),
+                    }
+                }
+                if column.name.is_empty() {
+                    raise!(0, "missing the field `name` of a column");
+                }
+                table.columns.push(column);
+            },
+            "rows" => for value in items(value, &key)? {
+                let row = fields(value, "a row")?;
+                if table.columns.is_empty() {
+                    for name in row.iter().map(|entry| &entry.0) {
+                        table.columns.push(new_column(token(name.clone())?));
+                    }
+                }
+                if row.len() != table.columns.len() {
+                    raise!(0, "found rows with different columns");
+                }
+                for (name, value) in row {
+                    let value = real(value, &name)?;
+                    match table.columns.iter_mut().find(|column| column.name == name) {
+                        Some(column) => column.data.push(value),
+                        _ => raise!(0, "found rows with different columns"),
+                    }
+                }
+            },
+            _ => raise!(0, "found an unknown field `{}`", key),
+        }
+    }
+    if table.name.is_empty() {
+        raise!(0, "missing the field `name` of a table");
+    }
+    if content::rows(&table).is_none() {
+        raise!(0, "found columns of different lengths in the table `{}`", table.name);
+    }
+    Ok(table)
+}
+
+fn record(value: Value, keys: &[&str]) -> Result<Vec<Option<usize>>> {
+    let mut values = vec![None; keys.len()];
+    for (key, value) in fields(value, "a record")? {
+        match keys.iter().position(|&name| name == key) {
+            Some(i) => values[i] = Some(natural(value, &key)?),
+            _ => raise!(0, "found an unknown field `{}`", key),
+        }
+    }
+    Ok(values)
+}
+
+fn required(value: Option<usize>, name: &str) -> Result<usize> {
+    match value {
+        Some(value) => Ok(value),
+        _ => raise!(0, "missing the field `{}`", name),
+    }
+}
+
+fn fields(value: Value, name: &str) -> Result<Vec<(String, Value)>> {
+    match value {
+        Value::Map(entries) => Ok(entries),
+        Value::Empty => Ok(vec![]),
+        _ => raise!(0, "expected a map for {}", name),
+    }
+}
+
+fn items(value: Value, name: &str) -> Result<Vec<Value>> {
+    match value {
+        Value::List(items) => Ok(items),
+        Value::Empty => Ok(vec![]),
+        _ => raise!(0, "expected a sequence for `{}`", name),
+    }
+}
+
+fn text(value: Value, name: &str) -> Result<String> {
+    match value {
+        Value::Text(value) => Ok(value),
+        _ => raise!(0, "expected a string for `{}`", name),
+    }
+}
+
+fn natural(value: Value, name: &str) -> Result<usize> {
+    match value {
+        Value::Natural(value) => Ok(value as usize),
+        Value::Real(value) if value.fract() == 0.0 && value >= 0.0 => Ok(value as usize),
+        _ => raise!(0, "expected a natural number for `{}`", name),
+    }
+}
+
+fn real(value: Value, name: &str) -> Result<f64> {
+    match value {
+        Value::Natural(value) => Ok(value as f64),
+        Value::Real(value) => Ok(value),
+        _ => raise!(0, "expected a real number for `{}`", name),
+    }
+}
+
+fn token(name: String) -> Result<String> {
+    let valid = name.chars().enumerate().all(|(i, c)| {
+        c.is_ascii_alphabetic() || i > 0 && (c == '_' || c.is_ascii_digit())
+    });
+    if !valid || name.is_empty() {
+        raise!(0, "found an invalid name `{}`", name);
+    }
+    Ok(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    const INPUT: &str = "@HYPERPERIOD 10
+
+@GRAPH 0 {
+\tPERIOD 10
+
+\tTASK t0_0\tTYPE 3
+\tTASK t0_1\tTYPE 4
+
+\tARC a0_0 \tFROM t0_0  TO  t0_1 TYPE 2
+
+\tHARD_DEADLINE d0_0 ON t0_1 AT 9
+}
+
+@CORE 0 {
+# price
+  10.5
+
+#------------------------------------------------------------------------------
+# type version execution_time
+  3    0       0.5
+  4    0       1.25
+}
+";
+
+    #[derive(Debug, Deserialize, PartialEq, Serialize)]
+    struct System {
+        attributes: Attributes,
+        graphs: Vec<Application>,
+        tables: Vec<Core>,
+    }
+
+    #[derive(Debug, Deserialize, PartialEq, Serialize)]
+    struct Attributes {
+        #[serde(rename = "HYPERPERIOD")]
+        hyperperiod: usize,
+    }
+
+    #[derive(Debug, Deserialize, PartialEq, Serialize)]
+    struct Application {
+        name: String,
+        attributes: HashMap<String, usize>,
+        tasks: Vec<Task>,
+        arcs: Vec<Arc>,
+        deadlines: Vec<Deadline>,
+    }
+
+    #[derive(Debug, Deserialize, PartialEq, Serialize)]
+    struct Task {
+        kind: u8,
+    }
+
+    #[derive(Debug, Deserialize, PartialEq, Serialize)]
+    struct Arc {
+        from: usize,
+        to: usize,
+        kind: usize,
+    }
+
+    #[derive(Debug, Deserialize, PartialEq, Serialize)]
+    struct Deadline {
+        on: usize,
+        at: u32,
+    }
+
+    #[derive(Debug, Deserialize, PartialEq, Serialize)]
+    struct Core {
+        name: String,
+        id: usize,
+        attributes: Price,
+        rows: Vec<Row>,
+    }
+
+    #[derive(Debug, Deserialize, PartialEq, Serialize)]
+    struct Price {
+        price: f64,
+    }
+
+    #[derive(Debug, Deserialize, PartialEq, Serialize)]
+    struct Row {
+        #[serde(rename = "type")]
+        kind: usize,
+        version: usize,
+        execution_time: Option<f64>,
+    }
+
+    #[test]
+    fn from_str() {
+        let system: System = super::from_str(INPUT).unwrap();
+        assert_eq!(system.attributes.hyperperiod, 10);
+        assert_eq!(system.graphs[0].name, "GRAPH");
+        assert_eq!(system.graphs[0].attributes["PERIOD"], 10);
+        assert_eq!(system.graphs[0].tasks, &[Task { kind: 3 }, Task { kind: 4 }]);
+        assert_eq!(system.graphs[0].arcs, &[Arc { from: 0, to: 1, kind: 2 }]);
+        assert_eq!(system.graphs[0].deadlines, &[Deadline { on: 1, at: 9 }]);
+        assert_eq!(system.tables[0].attributes.price, 10.5);
+        assert_eq!(system.tables[0].rows, &[
+            Row { kind: 3, version: 0, execution_time: Some(0.5) },
+            Row { kind: 4, version: 0, execution_time: Some(1.25) },
+        ]);
+
+        let error = super::from_str::<Vec<Task>>(INPUT).err().unwrap();
+        assert_eq!(error.line, 0);
+        let error = super::from_str::<System>("@GRAPH 0 {\n\tTASK t0_0 TYPE x\n}").err().unwrap();
+        assert_eq!(error.line, 2);
+    }
+
+    #[test]
+    fn to_string() {
+        let mut system: System = super::from_str(INPUT).unwrap();
+        assert_eq!(super::to_string(&system).unwrap(), INPUT);
+
+        system.tables[0].rows[1].execution_time = None;
+        let error = super::to_string(&system).err().unwrap();
+        assert_eq!(error.message, "found rows with different columns");
+
+        let mut content = ::parse(INPUT).unwrap();
+        content.tables[0].attributes.clear();
+        content.tables[0].columns[2].data.pop();
+        let error = super::to_string(&content).err().unwrap();
+        assert_eq!(error.message, "found columns of different lengths in the table `CORE`");
+        content.tables[0].columns[2].data.push(1.25);
+        assert!(super::to_string(&content).unwrap().ends_with("@CORE 0 {
+#------------------------------------------------------------------------------
+# type version execution_time
+  3    0       0.5
+  4    0       1.25
+}
+"));
+
+        system.graphs[0].name = String::from("TASK GRAPH");
+        let error = super::to_string(&system).err().unwrap();
+        assert_eq!(error.message, "found an invalid name `TASK GRAPH`");
+    }
+}
@@ -0,0 +1,458 @@
+//! Generation of random task graphs and data tables.
+//!
+//! Graphs are grown in the same way as by the `tgff` command-line utility: a
+//! graph starts with a single task and is expanded by alternating fan-out
+//! steps, which attach new successors to an existing task, and fan-in steps,
+//! which attach a new task to several existing ones. Every sink of a graph
+//! receives a hard deadline proportional to its depth, the period of a graph
+//! is derived from its latest deadline, and the hyperperiod is the least
+//! common multiple of the periods.
+//!
+//! Data tables describe processing elements. Each table has a row for every
+//! version of every task type, and the values of its columns and attributes
+//! are drawn according to specifications of the form `average multiplier
+//! jitter round`. A single uniform number in `[-1, 1]` is drawn per row and
+//! shared by all the columns of the row, so that, for instance, the tasks that
+//! consume more power also take longer to execute.
+
+use {Content, Graph, Table};
+use content::{self, new_arc, new_column, new_deadline, new_graph, new_table, new_task};
+use random::Generator;
+
+/// A configuration of graph generation.
+///
+/// The fields correspond to the parameters of TGFFOPT files given in
+/// parentheses.
+#[derive(Clone, Debug)]
+pub struct Config {
+    /// The seed of the random number generator (`seed`).
+    pub seed: u64,
+    /// The name of the graphs (`tg_label`).
+    pub label: String,
+    /// The number of graphs (`tg_cnt`).
+    pub graphs: usize,
+    /// The average number of tasks per graph and its maximal deviation
+    /// (`task_cnt`).
+    pub tasks: (f64, f64),
+    /// The maximal numbers of incoming and outgoing arcs of a task
+    /// (`task_degree`).
+    pub degree: (usize, usize),
+    /// The number of task types (`task_type_cnt`).
+    pub task_types: usize,
+    /// The number of arc types (`trans_type_cnt`).
+    pub arc_types: usize,
+    /// The average time per task including communication, which determines
+    /// deadlines (`task_trans_time`). Deadlines are whole numbers, so the time
+    /// should be expressed in units small enough for it to be well above one.
+    pub transfer_time: f64,
+    /// The relative deviation of deadlines (`deadline_jitter`).
+    pub deadline_jitter: f64,
+    /// The multipliers of the latest deadline a period is chosen from
+    /// (`period_mul`).
+    pub period_multipliers: Vec<f64>,
+}
+
+/// A configuration of table generation.
+///
+/// The fields correspond to the parameters of TGFFOPT files given in
+/// parentheses.
+#[derive(Clone, Debug)]
+pub struct Tables {
+    /// The seed of the random number generator (`seed`).
+    pub seed: u64,
+    /// The name of the tables (`table_label`).
+    pub label: String,
+    /// The number of tables (`table_cnt`).
+    pub count: usize,
+    /// The number of task types (`task_type_cnt`).
+    pub task_types: usize,
+    /// The number of versions of each task type.
+    pub versions: usize,
+    /// The price of a table (`table_attrib price`).
+    pub price: Attribute,
+    /// The other attributes of a table (`table_attrib`).
+    pub attributes: Vec<Attribute>,
+    /// The columns of a table (`type_attrib`).
+    pub columns: Vec<Attribute>,
+}
+
+/// A specification of how the values of an attribute are drawn.
+///
+/// A value is `average + multiplier * u`, where `u` is uniformly distributed
+/// in `[-1, 1]`, scaled by `1 + jitter * v`, where `v` is drawn independently
+/// in the same interval, and rounded to a multiple of `round`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Attribute {
+    /// The name of the attribute.
+    pub name: String,
+    /// The average value.
+    pub average: f64,
+    /// The range of the deviation of values of different types or tables.
+    pub multiplier: f64,
+    /// The range of the relative deviation of values that is not shared with
+    /// other attributes.
+    pub jitter: f64,
+    /// The granularity to round values to, which is zero for no rounding.
+    pub round: f64,
+}
+
+impl Default for Config {
+    fn default() -> Config {
+        Config {
+            seed: 0,
+            label: String::from("TASK_GRAPH"),
+            graphs: 1,
+            tasks: (10.0, 1.0),
+            degree: (2, 2),
+            task_types: 20,
+            arc_types: 20,
+            transfer_time: 1.0,
+            deadline_jitter: 0.0,
+            period_multipliers: vec![1.0],
+        }
+    }
+}
+
+impl Default for Tables {
+    fn default() -> Tables {
+        Tables {
+            seed: 0,
+            label: String::from("PE"),
+            count: 1,
+            task_types: 20,
+            versions: 1,
+            price: Attribute {
+                name: String::from("price"),
+                average: 10.0,
+                multiplier: 5.0,
+                jitter: 0.0,
+                round: 0.0,
+            },
+            attributes: vec![],
+            columns: vec![],
+        }
+    }
+}
+
+/// Generate task graphs.
+///
+/// The same configuration always produces the same graphs.
+pub fn generate(config: &Config) -> Content {
+    let mut generator = Generator::new(config.seed);
+    let mut content = content::new();
+
+    let mut hyperperiod = 1;
+    for id in 0..config.graphs {
+        let graph = grow(config, id, &mut generator);
+        if let Some(&period) = graph.attributes.get("PERIOD") {
+            hyperperiod = hyperperiod / gcd(hyperperiod, period) * period;
+        }
+        content.graphs.push(graph);
+    }
+    content.attributes.insert(String::from("HYPERPERIOD"), hyperperiod);
+
+    content
+}
+
+/// Generate data tables.
+///
+/// Each table has the attribute `price` followed by the other attributes, and
+/// its columns are `type`, `version`, and the requested ones. The same
+/// configuration always produces the same tables.
+pub fn tables(config: &Tables) -> Vec<Table> {
+    let mut generator = Generator::new(config.seed);
+    let mut tables = Vec::with_capacity(config.count);
+    for id in 0..config.count {
+        let mut table = new_table(config.label.clone(), id);
+        let shared = 2.0 * generator.uniform() - 1.0;
+        for attribute in Some(&config.price).into_iter().chain(config.attributes.iter()) {
+            let value = draw(attribute, shared, &mut generator);
+            table.attributes.insert(attribute.name.clone(), value);
+        }
+
+        table.columns.push(new_column(String::from("type")));
+        table.columns.push(new_column(String::from("version")));
+        for attribute in config.columns.iter() {
+            table.columns.push(new_column(attribute.name.clone()));
+        }
+        for kind in 0..config.task_types {
+            for version in 0..config.versions {
+                table.columns[0].data.push(kind as f64);
+                table.columns[1].data.push(version as f64);
+                let shared = 2.0 * generator.uniform() - 1.0;
+                for (k, attribute) in config.columns.iter().enumerate() {
+                    let value = draw(attribute, shared, &mut generator);
+                    table.columns[k + 2].data.push(value);
+                }
+            }
+        }
+        tables.push(table);
+    }
+    tables
+}
+
+/// Assign hard deadlines to the sinks of a task graph and set its period.
+///
+/// The deadline of a sink is the time per task multiplied by the number of
+/// tasks on the longest path ending at the sink, and the period is the latest
+/// deadline. Since deadlines are whole numbers, they are rounded to the
+/// nearest integer and are at least one, so that times per task well below
+/// one make all deadlines equal to one; such times should be scaled to
+/// smaller units beforehand. The function returns `None` if the graph is not
+/// acyclic.
+pub fn deadlines(graph: &mut Graph, transfer_time: f64) -> Option<()> {
+    let latest = match assign(graph, transfer_time, 0.0, &mut Generator::new(0)) {
+        Some(latest) => latest,
+        _ => return None,
+    };
+    graph.attributes.insert(String::from("PERIOD"), latest);
+    Some(())
+}
+
+fn draw(attribute: &Attribute, shared: f64, generator: &mut Generator) -> f64 {
+    let jitter = attribute.jitter * (2.0 * generator.uniform() - 1.0);
+    let value = (attribute.average + attribute.multiplier * shared) * (1.0 + jitter);
+    if attribute.round > 0.0 {
+        (value / attribute.round).round() * attribute.round
+    } else {
+        value
+    }
+}
+
+fn grow(config: &Config, id: usize, generator: &mut Generator) -> Graph {
+    let (average, deviation) = config.tasks;
+    let count = (average + deviation * (2.0 * generator.uniform() - 1.0)).round().max(1.0);
+    let count = count as usize;
+    let (fan_in, fan_out) = config.degree;
+
+    let mut graph = new_graph(config.label.clone(), id);
+    let mut incoming = vec![0];
+    let mut outgoing = vec![0];
+    let mut arcs = vec![];
+    while incoming.len() < count {
+        let mut sources = (0..incoming.len()).filter(|&i| outgoing[i] < fan_out)
+                                             .collect::<Vec<_>>();
+        if sources.is_empty() {
+            incoming.push(0);
+            outgoing.push(0);
+            continue;
+        }
+        if fan_in >= 2 && sources.len() >= 2 && generator.uniform() < 0.5 {
+            let limit = fan_in.min(sources.len());
+            let degree = 2 + generator.below(limit - 1);
+            generator.shuffle(&mut sources);
+            sources.truncate(degree);
+            sources.sort();
+            let task = incoming.len();
+            for &source in sources.iter() {
+                arcs.push((source, task));
+                outgoing[source] += 1;
+            }
+            incoming.push(degree);
+            outgoing.push(0);
+        } else {
+            let source = sources[generator.below(sources.len())];
+            let limit = (fan_out - outgoing[source]).min(count - incoming.len());
+            for _ in 0..(1 + generator.below(limit)) {
+                arcs.push((source, incoming.len()));
+                outgoing[source] += 1;
+                incoming.push(1);
+                outgoing.push(0);
+            }
+        }
+    }
+
+    for i in 0..count {
+        graph.tasks.push(new_task(i, generator.below(config.task_types.max(1))));
+    }
+    for (k, &(from, to)) in arcs.iter().enumerate() {
+        graph.arcs.push(new_arc(k, from, to, generator.below(config.arc_types.max(1))));
+    }
+
+    let latest = assign(&mut graph, config.transfer_time, config.deadline_jitter,
+                        generator).unwrap();
+    let multiplier = match config.period_multipliers.len() {
+        0 => 1.0,
+        length => config.period_multipliers[generator.below(length)],
+    };
+    let period = (latest as f64 * multiplier).round().max(1.0) as usize;
+    graph.attributes.insert(String::from("PERIOD"), period);
+
+    graph
+}
+
+fn assign(graph: &mut Graph, transfer_time: f64, jitter: f64, generator: &mut Generator)
+          -> Option<usize> {
+
+    let dag = match ::dag::new(graph) {
+        Some(dag) => dag,
+        _ => return None,
+    };
+    let mut depth = vec![0; graph.tasks.len()];
+    for &i in dag.order.iter() {
+        for &(j, _) in dag.successors[i].iter() {
+            depth[j] = depth[j].max(depth[i] + 1);
+        }
+    }
+
+    let mut latest = 1;
+    for i in (0..graph.tasks.len()).filter(|&i| dag.successors[i].is_empty()) {
+        let jitter = jitter * (2.0 * generator.uniform() - 1.0);
+        let at = (transfer_time * (depth[i] + 1) as f64 * (1.0 + jitter)).round();
+        let at = at.max(1.0) as usize;
+        latest = latest.max(at);
+        let (id, on) = (graph.deadlines.len(), graph.tasks[i].id);
+        graph.deadlines.push(new_deadline(id, on, at));
+    }
+    Some(latest)
+}
+
+pub(crate) fn gcd(mut one: usize, mut other: usize) -> usize {
+    while other != 0 {
+        let rest = one % other;
+        one = other;
+        other = rest;
+    }
+    one
+}
+
+#[cfg(test)]
+mod tests {
+    use content::{new_arc, new_graph, new_task};
+    use super::{Attribute, Config, Tables};
+
+    #[test]
+    fn generate() {
+        let config = Config {
+            seed: 4,
+            label: String::from("GRAPH"),
+            graphs: 3,
+            tasks: (40.0, 0.001),
+            degree: (3, 4),
+            period_multipliers: vec![1.0, 2.0, 3.0],
+            ..Config::default()
+        };
+        let content = super::generate(&config);
+        assert_eq!(content.graphs.len(), 3);
+
+        let mut hyperperiod = 1;
+        for graph in content.graphs.iter() {
+            assert_eq!(graph.name, "GRAPH");
+            assert_eq!(graph.tasks.len(), 40);
+            assert!(::dag::new(graph).is_some());
+            for task in graph.tasks.iter() {
+                let incoming = graph.arcs.iter().filter(|arc| arc.to == task.id).count();
+                let outgoing = graph.arcs.iter().filter(|arc| arc.from == task.id).count();
+                assert!(incoming <= 3 && outgoing <= 4);
+                assert!(task.kind < 20);
+                let deadline = graph.deadlines.iter().find(|deadline| deadline.on == task.id);
+                assert_eq!(deadline.is_some(), outgoing == 0);
+            }
+            let period = graph.attributes["PERIOD"];
+            assert!(graph.deadlines.iter().all(|deadline| deadline.at >= 1));
+            assert_eq!(period % graph.deadlines.iter().map(|deadline| deadline.at).max().unwrap(),
+                       0);
+            assert_eq!(content.attributes["HYPERPERIOD"] % period, 0);
+            hyperperiod = hyperperiod.max(period);
+        }
+        assert!(content.attributes["HYPERPERIOD"] >= hyperperiod);
+
+        let other = super::generate(&config);
+        for (one, other) in content.graphs.iter().zip(other.graphs.iter()) {
+            assert_eq!(one.tasks.iter().map(|task| task.kind).collect::<Vec<_>>(),
+                       other.tasks.iter().map(|task| task.kind).collect::<Vec<_>>());
+            assert_eq!(one.arcs.iter().map(|arc| (arc.from, arc.to, arc.kind)).collect::<Vec<_>>(),
+                       other.arcs.iter().map(|arc| (arc.from, arc.to, arc.kind))
+                                        .collect::<Vec<_>>());
+            assert_eq!(one.deadlines.iter().map(|deadline| deadline.at).collect::<Vec<_>>(),
+                       other.deadlines.iter().map(|deadline| deadline.at).collect::<Vec<_>>());
+        }
+
+        let config = Config { seed: 5, ..config };
+        let other = super::generate(&config);
+        assert!(content.graphs[0].arcs.iter().zip(other.graphs[0].arcs.iter()).any(|(one, other)| {
+            (one.from, one.to, one.kind) != (other.from, other.to, other.kind)
+        }));
+    }
+
+    #[test]
+    fn deadlines() {
+        let mut graph = new_graph(String::from("GRAPH"), 0);
+        for i in 0..5 {
+            graph.tasks.push(new_task(i, 0));
+        }
+        for i in 0..3 {
+            graph.arcs.push(new_arc(i, i, i + 1, 0));
+        }
+
+        let mut other = graph.clone();
+        super::deadlines(&mut other, 1.5).unwrap();
+        assert_eq!(other.deadlines.iter().map(|deadline| (deadline.on, deadline.at))
+                                         .collect::<Vec<_>>(), vec![(3, 6), (4, 2)]);
+        assert_eq!(other.attributes["PERIOD"], 6);
+
+        let mut other = graph.clone();
+        super::deadlines(&mut other, 0.05).unwrap();
+        assert_eq!(other.deadlines.iter().map(|deadline| (deadline.on, deadline.at))
+                                         .collect::<Vec<_>>(), vec![(3, 1), (4, 1)]);
+        assert_eq!(other.attributes["PERIOD"], 1);
+
+        graph.arcs.push(new_arc(3, 3, 0, 0));
+        assert!(super::deadlines(&mut graph, 1.0).is_none());
+    }
+
+    #[test]
+    fn tables() {
+        let attribute = |name: &str, average, multiplier, jitter, round| {
+            Attribute {
+                name: String::from(name),
+                average: average,
+                multiplier: multiplier,
+                jitter: jitter,
+                round: round,
+            }
+        };
+        let config = Tables {
+            seed: 4,
+            label: String::from("CORE"),
+            count: 3,
+            task_types: 5,
+            versions: 2,
+            price: attribute("price", 10.0, 5.0, 0.0, 0.0),
+            attributes: vec![attribute("area", 2.0, 0.0, 0.5, 0.0)],
+            columns: vec![
+                attribute("dynamic_power", 10.0, 9.0, 0.0, 0.01),
+                attribute("execution_time", 0.020, 0.010, 0.0, 0.001),
+            ],
+        };
+        let tables = super::tables(&config);
+        assert_eq!(tables.len(), 3);
+        for (i, table) in tables.iter().enumerate() {
+            assert_eq!((&table.name[..], table.id), ("CORE", i));
+            assert!((5.0..=15.0).contains(&table.attributes["price"]));
+            assert!((1.0..=3.0).contains(&table.attributes["area"]));
+            assert_eq!(table.columns.iter().map(|column| &column.name[..]).collect::<Vec<_>>(),
+                       vec!["type", "version", "dynamic_power", "execution_time"]);
+            assert_eq!(table.columns[0].data, vec![0.0, 0.0, 1.0, 1.0, 2.0, 2.0, 3.0, 3.0,
+                                                   4.0, 4.0]);
+            assert_eq!(table.columns[1].data, vec![0.0, 1.0, 0.0, 1.0, 0.0, 1.0, 0.0, 1.0,
+                                                   0.0, 1.0]);
+            for (&power, &time) in table.columns[2].data.iter().zip(&table.columns[3].data) {
+                assert!((1.0..=19.0).contains(&power));
+                assert!(((power - 10.0) / 9.0 - (time - 0.02) / 0.01).abs() < 0.1);
+                assert!(((time / 0.001).round() * 0.001 - time).abs() < 1e-12);
+            }
+        }
+
+        assert!(tables[0].columns[2].data != tables[1].columns[2].data);
+        assert!(tables[0].columns[3].data != tables[2].columns[3].data);
+
+        let other = super::tables(&config);
+        for (one, other) in tables.iter().zip(other.iter()) {
+            assert_eq!(one.attributes, other.attributes);
+            for (one, other) in one.columns.iter().zip(other.columns.iter()) {
+                assert_eq!(one.data, other.data);
+            }
+        }
+    }
+}
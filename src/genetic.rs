@@ -0,0 +1,413 @@
+//! Genetic optimization of mappings and priorities.
+//!
+//! A chromosome encodes both the processing element and version of each task
+//! and a priority list, which are decoded into a schedule by the list
+//! scheduler. Fitness evaluation is distributed over a number of threads, and
+//! the outcome depends only on the seed regardless of the number of threads.
+
+use std::thread;
+
+use mapping::{Assignment, Mapping};
+use optimization::{Objective, Problem, Solution, Step};
+use random::Generator;
+use schedule::Schedule;
+
+/// A chromosome.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Chromosome {
+    /// The processing element and version of each task in the order of the
+    /// tasks in the graph.
+    pub genes: Vec<(usize, usize)>,
+    /// The positions of the tasks in the order of decreasing priority.
+    pub order: Vec<usize>,
+}
+
+/// A crossover operator for the mapping part of chromosomes.
+///
+/// The priority lists are always recombined using order crossover, which
+/// keeps the outcome a permutation.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Crossover {
+    /// Exchange the genes after a random point.
+    OnePoint,
+    /// Exchange the genes between two random points.
+    TwoPoint,
+    /// Exchange each gene with probability one half.
+    Uniform,
+}
+
+/// A configuration of genetic optimization.
+#[derive(Clone, Debug)]
+pub struct Config {
+    /// The seed of the random number generator.
+    pub seed: u64,
+    /// The number of chromosomes in the population.
+    pub population: usize,
+    /// The number of generations.
+    pub generations: usize,
+    /// The crossover operator.
+    pub crossover: Crossover,
+    /// The probability of crossover of two parents.
+    pub crossover_rate: f64,
+    /// The probability of mutation of each gene.
+    pub mutation_rate: f64,
+    /// The number of the best chromosomes copied to the next generation
+    /// unchanged.
+    pub elitism: usize,
+    /// The size of tournaments used for selecting parents.
+    pub tournament: usize,
+    /// The number of threads used for evaluation.
+    pub threads: usize,
+}
+
+impl Default for Config {
+    fn default() -> Config {
+        Config {
+            seed: 0,
+            population: 50,
+            generations: 100,
+            crossover: Crossover::TwoPoint,
+            crossover_rate: 0.9,
+            mutation_rate: 0.02,
+            elitism: 2,
+            tournament: 3,
+            threads: 1,
+        }
+    }
+}
+
+impl Chromosome {
+    /// Encode a mapping.
+    ///
+    /// The assignments are expected to follow the order of the tasks in the
+    /// graph.
+    pub fn encode(mapping: &Mapping) -> Chromosome {
+        let ref assignments = mapping.assignments;
+        let mut order = (0..assignments.len()).collect::<Vec<_>>();
+        order.sort_by_key(|&i| (assignments[i].priority, i));
+        Chromosome {
+            genes: assignments.iter().map(|assignment| {
+                (assignment.element, assignment.version)
+            }).collect(),
+            order: order,
+        }
+    }
+
+    /// Decode into a mapping.
+    pub fn decode(&self, problem: &Problem) -> Mapping {
+        let mut priorities = vec![0; self.order.len()];
+        for (priority, &i) in self.order.iter().enumerate() {
+            priorities[i] = priority;
+        }
+        Mapping {
+            assignments: problem.graph.tasks.iter().enumerate().map(|(i, task)| Assignment {
+                task: task.id,
+                element: self.genes[i].0,
+                version: self.genes[i].1,
+                priority: priorities[i],
+            }).collect(),
+        }
+    }
+}
+
+/// Minimize an objective using a genetic algorithm.
+///
+/// The initial population contains the solution produced by HEFT and random
+/// chromosomes. Parents are chosen by tournament selection, and the best
+/// chromosomes survive to the next generation. The function returns `None`
+/// if some task cannot be executed by any processing element or if the
+/// population is empty.
+pub fn evolve<T>(problem: &Problem, objective: &T, config: &Config) -> Option<Solution>
+    where T: Objective + Sync + ?Sized
+{
+    let count = problem.graph.tasks.len();
+    if config.population == 0 || (0..count).any(|i| problem.choices(i).is_empty()) {
+        return None;
+    }
+
+    let mut generator = Generator::new(config.seed);
+    let mut population = Vec::with_capacity(config.population);
+    if let Some(mapping) = problem.initial() {
+        population.push(Chromosome::encode(&mapping));
+    }
+    while population.len() < config.population {
+        let mut order = (0..count).collect::<Vec<_>>();
+        generator.shuffle(&mut order);
+        population.push(Chromosome {
+            genes: (0..count).map(|i| random(problem, i, &mut generator)).collect(),
+            order: order,
+        });
+    }
+
+    let mut best: Option<Solution> = None;
+    let mut log = Vec::with_capacity(config.generations + 1);
+    for generation in 0..(config.generations + 1) {
+        let mappings = population.iter().map(|chromosome| chromosome.decode(problem))
+                                        .collect::<Vec<_>>();
+        let outcomes = evaluate(problem, objective, &mappings, config.threads);
+
+        let mut ranking = (0..population.len()).collect::<Vec<_>>();
+        let costs = outcomes.iter().map(|outcome| match *outcome {
+            Some((_, cost)) if !cost.is_nan() => cost,
+            _ => f64::INFINITY,
+        }).collect::<Vec<_>>();
+        ranking.sort_by(|&i, &j| costs[i].partial_cmp(&costs[j]).unwrap().then(i.cmp(&j)));
+
+        let leader = ranking[0];
+        let improved = match best {
+            Some(ref solution) => costs[leader] < solution.cost,
+            _ => outcomes[leader].is_some(),
+        };
+        if improved {
+            let (schedule, cost) = outcomes[leader].clone().unwrap();
+            best = Some(Solution {
+                mapping: mappings[leader].clone(),
+                schedule: schedule,
+                cost: cost,
+                log: vec![],
+            });
+        }
+        log.push(Step {
+            current: costs[leader],
+            best: best.as_ref().map_or(f64::INFINITY, |solution| solution.cost),
+        });
+        if generation == config.generations {
+            break;
+        }
+
+        let mut next = ranking.iter().take(config.elitism.min(config.population))
+                                     .map(|&i| population[i].clone())
+                                     .collect::<Vec<_>>();
+        while next.len() < config.population {
+            let first = select(&costs, config.tournament, &mut generator);
+            let second = select(&costs, config.tournament, &mut generator);
+            let (mut one, mut other) = if generator.uniform() < config.crossover_rate {
+                cross(&population[first], &population[second], config.crossover, &mut generator)
+            } else {
+                (population[first].clone(), population[second].clone())
+            };
+            mutate(problem, &mut one, config.mutation_rate, &mut generator);
+            mutate(problem, &mut other, config.mutation_rate, &mut generator);
+            next.push(one);
+            if next.len() < config.population {
+                next.push(other);
+            }
+        }
+        population = next;
+    }
+
+    best.map(|mut solution| {
+        solution.log = log;
+        solution
+    })
+}
+
+fn evaluate<T>(problem: &Problem, objective: &T, mappings: &[Mapping], threads: usize)
+               -> Vec<Option<(Schedule, f64)>>
+    where T: Objective + Sync + ?Sized
+{
+    parallel(mappings, threads, |mapping| problem.evaluate(objective, mapping))
+}
+
+/// Apply a function to each item using a number of threads while preserving
+/// the order of the outcomes.
+pub(crate) fn parallel<T, U, F>(items: &[T], threads: usize, function: F) -> Vec<U>
+    where T: Sync, U: Send, F: Fn(&T) -> U + Sync
+{
+    if threads <= 1 || items.len() <= 1 {
+        return items.iter().map(function).collect();
+    }
+    let size = items.len().div_ceil(threads);
+    let function = &function;
+    thread::scope(|scope| {
+        let handles = items.chunks(size).map(|chunk| {
+            scope.spawn(move || chunk.iter().map(function).collect::<Vec<_>>())
+        }).collect::<Vec<_>>();
+        handles.into_iter().flat_map(|handle| handle.join().unwrap()).collect()
+    })
+}
+
+pub(crate) fn random(problem: &Problem, task: usize, generator: &mut Generator) -> (usize, usize) {
+    let choices = problem.choices(task);
+    let (element, ref versions) = choices[generator.below(choices.len())];
+    (element, versions[generator.below(versions.len())])
+}
+
+fn select(costs: &[f64], size: usize, generator: &mut Generator) -> usize {
+    let mut best = generator.below(costs.len());
+    for _ in 1..size {
+        let other = generator.below(costs.len());
+        if costs[other] < costs[best] {
+            best = other;
+        }
+    }
+    best
+}
+
+pub(crate) fn cross(one: &Chromosome, other: &Chromosome, crossover: Crossover,
+                    generator: &mut Generator) -> (Chromosome, Chromosome) {
+
+    let count = one.genes.len();
+    let (mut first, mut second) = (one.clone(), other.clone());
+    if count < 2 {
+        return (first, second);
+    }
+
+    let (begin, end) = match crossover {
+        Crossover::OnePoint => (1 + generator.below(count - 1), count),
+        _ => {
+            let (a, b) = (generator.below(count), generator.below(count));
+            (a.min(b), a.max(b) + 1)
+        },
+    };
+    for i in 0..count {
+        let exchange = match crossover {
+            Crossover::Uniform => generator.uniform() < 0.5,
+            _ => i >= begin && i < end,
+        };
+        if exchange {
+            first.genes[i] = other.genes[i];
+            second.genes[i] = one.genes[i];
+        }
+    }
+
+    first.order = order(&one.order, &other.order, begin, end);
+    second.order = order(&other.order, &one.order, begin, end);
+
+    (first, second)
+}
+
+/// Perform order crossover: the segment `[begin, end)` is taken from the first
+/// parent, and the remaining positions are filled with the missing elements in
+/// the order they appear in the second parent.
+fn order(one: &[usize], other: &[usize], begin: usize, end: usize) -> Vec<usize> {
+    let count = one.len();
+    let mut taken = vec![false; count];
+    for &i in one[begin..end].iter() {
+        taken[i] = true;
+    }
+    let mut rest = other.iter().filter(|&&i| !taken[i]);
+    (0..count).map(|k| {
+        if k >= begin && k < end { one[k] } else { *rest.next().unwrap() }
+    }).collect()
+}
+
+pub(crate) fn mutate(problem: &Problem, chromosome: &mut Chromosome, rate: f64,
+                     generator: &mut Generator) {
+
+    let count = chromosome.genes.len();
+    for i in 0..count {
+        if generator.uniform() < rate {
+            chromosome.genes[i] = random(problem, i, generator);
+        }
+        if generator.uniform() < rate {
+            let j = generator.below(count);
+            chromosome.order.swap(i, j);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use communication::Communication;
+    use optimization::{Makespan, Problem};
+    use platform::Platform;
+    use random::Generator;
+    use super::{Chromosome, Config, Crossover};
+
+    const INPUT: &str = "@GRAPH 0 {
+    TASK t0_0 TYPE 0
+    TASK t0_1 TYPE 1
+    TASK t0_2 TYPE 1
+    TASK t0_3 TYPE 1
+    TASK t0_4 TYPE 0
+    ARC a0_0 FROM t0_0 TO t0_1 TYPE 0
+    ARC a0_1 FROM t0_0 TO t0_2 TYPE 0
+    ARC a0_2 FROM t0_0 TO t0_3 TYPE 0
+    ARC a0_3 FROM t0_1 TO t0_4 TYPE 0
+    ARC a0_4 FROM t0_2 TO t0_4 TYPE 0
+    ARC a0_5 FROM t0_3 TO t0_4 TYPE 0
+}
+@CORE 0 {
+# price
+  1
+#----
+# type version execution_time
+  0    0       1
+  1    0       4
+}
+@CORE 1 {
+# price
+  1
+#----
+# type version execution_time
+  0    0       1
+  1    0       4
+  1    1       2
+}
+@CORE 2 {
+# price
+  1
+#----
+# type version execution_time
+  1    0       3
+}
+";
+
+    #[test]
+    fn encode() {
+        let content = ::parse(INPUT).unwrap();
+        let platform = Platform::new(&content, "CORE");
+        let communication = Communication::new(1.0);
+        let problem = Problem::new(&content.graphs[0], &platform, &communication);
+
+        let mapping = problem.initial().unwrap();
+        let chromosome = Chromosome::encode(&mapping);
+        assert_eq!(chromosome.decode(&problem), mapping);
+    }
+
+    #[test]
+    fn order() {
+        let one = vec![0, 1, 2, 3, 4, 5];
+        let other = vec![5, 4, 3, 2, 1, 0];
+        assert_eq!(super::order(&one, &other, 2, 4), vec![5, 4, 2, 3, 1, 0]);
+        assert_eq!(super::order(&one, &other, 0, 6), one);
+    }
+
+    #[test]
+    fn cross() {
+        let one = Chromosome { genes: vec![(0, 0); 6], order: vec![0, 1, 2, 3, 4, 5] };
+        let other = Chromosome { genes: vec![(1, 1); 6], order: vec![5, 4, 3, 2, 1, 0] };
+        let mut generator = Generator::new(3);
+        for &crossover in [Crossover::OnePoint, Crossover::TwoPoint, Crossover::Uniform].iter() {
+            for _ in 0..20 {
+                let (first, second) = super::cross(&one, &other, crossover, &mut generator);
+                for i in 0..6 {
+                    assert!(first.genes[i] != second.genes[i]);
+                }
+                let mut order = first.order.clone();
+                order.sort();
+                assert_eq!(order, one.order);
+            }
+        }
+    }
+
+    #[test]
+    fn evolve() {
+        let content = ::parse(INPUT).unwrap();
+        let platform = Platform::new(&content, "CORE");
+        let communication = Communication::new(1.0);
+        let problem = Problem::new(&content.graphs[0], &platform, &communication);
+
+        let config = Config { seed: 1, population: 20, generations: 30, ..Config::default() };
+        let solution = super::evolve(&problem, &Makespan, &config).unwrap();
+        assert_eq!(solution.cost, 7.0);
+        assert_eq!(solution.log.len(), 31);
+        assert!(solution.log.windows(2).all(|steps| steps[1].best <= steps[0].best));
+
+        let config = Config { threads: 4, ..config };
+        let other = super::evolve(&problem, &Makespan, &config).unwrap();
+        assert_eq!(solution.log, other.log);
+        assert_eq!(solution.mapping, other.mapping);
+    }
+}
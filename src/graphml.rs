@@ -0,0 +1,578 @@
+//! GraphML descriptions of task graphs.
+//!
+//! Each task graph is written as a `graph` element whose ID is composed of
+//! the name and ID of the graph, such as `GRAPH_0`. A task is written as a
+//! node, and an arc as an edge, both with IDs named as in TGFF files, such as
+//! `t0_1` and `a0_1`. Task types, arc types, hard deadlines and attributes,
+//! such as `HYPERPERIOD` and `PERIOD`, are written as data whose keys are
+//! declared with the type `long`.
+
+use std::collections::{BTreeSet, HashMap};
+use std::fmt::Write;
+
+use {Content, Error, Graph, Result};
+use content::{self, new_arc, new_deadline, new_graph, new_task};
+use lexer::{natural, title};
+
+/// Write the task graphs of a content in the GraphML format.
+pub fn write(content: &Content) -> String {
+    let mut output = String::new();
+    writeln!(output, "<?xml version=\"1.0\" encoding=\"UTF-8\"?>").unwrap();
+    writeln!(output, "<graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\">").unwrap();
+
+    let names = content.graphs.iter().flat_map(|graph| graph.attributes.keys())
+                                     .collect::<BTreeSet<_>>();
+    let key = |output: &mut String, domain: &str, name: &str| {
+        writeln!(output, "  <key id=\"{}_{}\" for=\"{}\" attr.name=\"{}\" attr.type=\"long\"/>",
+                 domain, escape(name), domain, escape(name)).unwrap();
+    };
+    for name in content.attributes.keys().collect::<BTreeSet<_>>() {
+        key(&mut output, "graphml", name);
+    }
+    for name in names {
+        key(&mut output, "graph", name);
+    }
+    key(&mut output, "node", "type");
+    key(&mut output, "node", "deadline");
+    key(&mut output, "edge", "type");
+
+    for (name, value) in sorted(&content.attributes) {
+        writeln!(output, "  <data key=\"graphml_{}\">{}</data>", escape(name), value).unwrap();
+    }
+    for graph in content.graphs.iter() {
+        let id = graph.id;
+        writeln!(output, "  <graph id=\"{}_{}\" edgedefault=\"directed\">", escape(&graph.name),
+                 id).unwrap();
+        for (name, value) in sorted(&graph.attributes) {
+            writeln!(output, "    <data key=\"graph_{}\">{}</data>", escape(name), value).unwrap();
+        }
+        for task in graph.tasks.iter() {
+            write!(output, "    <node id=\"t{}_{}\"><data key=\"node_type\">{}</data>", id, task.id,
+                   task.kind).unwrap();
+            for deadline in graph.deadlines.iter().filter(|deadline| deadline.on == task.id) {
+                write!(output, "<data key=\"node_deadline\">{}</data>", deadline.at).unwrap();
+            }
+            writeln!(output, "</node>").unwrap();
+        }
+        for arc in graph.arcs.iter() {
+            writeln!(output, "    <edge id=\"a{}_{}\" source=\"t{}_{}\" target=\"t{}_{}\">\
+                              <data key=\"edge_type\">{}</data></edge>", id, arc.id, id, arc.from,
+                     id, arc.to, arc.kind).unwrap();
+        }
+        writeln!(output, "  </graph>").unwrap();
+    }
+
+    writeln!(output, "</graphml>").unwrap();
+    output
+}
+
+/// Read task graphs from a GraphML description.
+///
+/// Data are interpreted by the names of the keys they refer to rather than by
+/// the IDs of the keys, and only data whose keys have numeric types are read.
+/// The types of tasks and arcs are taken from the data called `type` of nodes
+/// and edges, respectively, and hard deadlines from the data called
+/// `deadline` of nodes. The data of graphs and documents are read as
+/// attributes. Tasks are numbered in the order of their nodes, and arcs in
+/// the order of their edges. Tasks and arcs without types get type zero. If
+/// the ID of a graph ends with an underscore followed by a number, as in
+/// `GRAPH_0`, the number is taken as the ID of the graph. Elements other than
+/// keys, graphs, nodes, edges and data are skipped.
+pub fn read(input: &str) -> Result<Content> {
+    let root = Parser { chars: input.chars().collect(), position: 0, line: 1 }.document()?;
+    if root.name != "graphml" {
+        raise!(root.line, "expected a `graphml` element");
+    }
+
+    let mut keys = HashMap::new();
+    for element in root.children.iter().filter(|element| element.name == "key") {
+        let id = element.attribute("id")?;
+        let default = element.children.iter().find(|child| child.name == "default")
+                                              .map(|child| (child.text.trim(), child.line));
+        keys.insert(id, Key {
+            domain: element.find("for").unwrap_or("all"),
+            name: element.find("attr.name").unwrap_or(id),
+            numeric: element.find("attr.type").is_some_and(|kind| {
+                ["int", "long", "float", "double"].contains(&kind)
+            }),
+            default: default,
+        });
+    }
+
+    let mut content = content::new();
+    for (name, value, line) in data(&root, "graphml", &keys)? {
+        content.attributes.insert(name.to_string(), natural(value, name, line)?);
+    }
+    for element in root.children.iter().filter(|element| element.name == "graph") {
+        content.graphs.push(graph(element, &keys)?);
+    }
+
+    Ok(content)
+}
+
+struct Key<'a> {
+    domain: &'a str,
+    name: &'a str,
+    numeric: bool,
+    default: Option<(&'a str, usize)>,
+}
+
+struct Element {
+    name: String,
+    attributes: Vec<(String, String)>,
+    children: Vec<Element>,
+    text: String,
+    line: usize,
+}
+
+struct Parser {
+    chars: Vec<char>,
+    position: usize,
+    line: usize,
+}
+
+impl Element {
+    fn find(&self, name: &str) -> Option<&str> {
+        self.attributes.iter().find(|attribute| attribute.0 == name)
+                              .map(|attribute| &attribute.1[..])
+    }
+
+    fn attribute(&self, name: &str) -> Result<&str> {
+        match self.find(name) {
+            Some(value) => Ok(value),
+            _ => raise!(self.line, "missing the attribute `{}` of a `{}` element", name,
+                        self.name),
+        }
+    }
+}
+
+impl Parser {
+    fn document(&mut self) -> Result<Element> {
+        loop {
+            self.skip_whitespace();
+            if self.starts_with("<?") {
+                self.skip_until("?>")?;
+            } else if self.starts_with("<!--") {
+                self.skip_until("-->")?;
+            } else if self.starts_with("<!") {
+                self.skip_declaration()?;
+            } else if self.starts_with("<") {
+                self.advance(1);
+                let element = self.element()?;
+                self.skip_whitespace();
+                while self.starts_with("<!--") || self.starts_with("<?") {
+                    if self.starts_with("<!--") {
+                        self.skip_until("-->")?;
+                    } else {
+                        self.skip_until("?>")?;
+                    }
+                    self.skip_whitespace();
+                }
+                if self.peek().is_some() {
+                    raise!(self.line, "found content after the root element");
+                }
+                return Ok(element);
+            } else {
+                raise!(self.line, "expected an element");
+            }
+        }
+    }
+
+    fn element(&mut self) -> Result<Element> {
+        let line = self.line;
+        let name = self.name();
+        if name.is_empty() {
+            raise!(line, "expected the name of an element");
+        }
+        let mut element = Element {
+            name: local(&name).to_string(),
+            attributes: vec![],
+            children: vec![],
+            text: String::new(),
+            line: line,
+        };
+
+        loop {
+            self.skip_whitespace();
+            if self.starts_with("/>") {
+                self.advance(2);
+                return Ok(element);
+            }
+            if self.starts_with(">") {
+                self.advance(1);
+                break;
+            }
+            let attribute = self.name();
+            if attribute.is_empty() {
+                raise!(self.line, "expected the name of an attribute");
+            }
+            self.skip_whitespace();
+            self.expect('=')?;
+            self.skip_whitespace();
+            let quote = match self.peek() {
+                Some(quote) if quote == '"' || quote == '\'' => quote,
+                _ => raise!(self.line, "expected a quoted value"),
+            };
+            self.advance(1);
+            let mut value = String::new();
+            loop {
+                match self.peek() {
+                    Some(c) if c == quote => break,
+                    Some(c) => value.push(c),
+                    _ => raise!(self.line, "found an unterminated value"),
+                }
+                self.advance(1);
+            }
+            self.advance(1);
+            element.attributes.push((local(&attribute).to_string(), decode(&value, self.line)?));
+        }
+
+        loop {
+            if self.starts_with("</") {
+                self.advance(2);
+                let closing = self.name();
+                if closing != name {
+                    raise!(self.line, "expected the closing tag of `{}`", name);
+                }
+                self.skip_whitespace();
+                self.expect('>')?;
+                return Ok(element);
+            } else if self.starts_with("<!--") {
+                self.skip_until("-->")?;
+            } else if self.starts_with("<![CDATA[") {
+                self.advance(9);
+                let start = self.position;
+                self.skip_until("]]>")?;
+                let text = self.chars[start..(self.position - 3)].iter().collect::<String>();
+                element.text.push_str(&text);
+            } else if self.starts_with("<?") {
+                self.skip_until("?>")?;
+            } else if self.starts_with("<") {
+                self.advance(1);
+                let child = self.element()?;
+                element.children.push(child);
+            } else if self.peek().is_some() {
+                let line = self.line;
+                let mut text = String::new();
+                while let Some(c) = self.peek() {
+                    if c == '<' {
+                        break;
+                    }
+                    text.push(c);
+                    self.advance(1);
+                }
+                element.text.push_str(&decode(&text, line)?);
+            } else {
+                raise!(self.line, "expected the closing tag of `{}`", name);
+            }
+        }
+    }
+
+    fn name(&mut self) -> String {
+        let mut name = String::new();
+        while let Some(c) = self.peek() {
+            if c.is_whitespace() || c == '/' || c == '>' || c == '=' {
+                break;
+            }
+            name.push(c);
+            self.advance(1);
+        }
+        name
+    }
+
+    fn skip_declaration(&mut self) -> Result<()> {
+        let mut depth = 0;
+        loop {
+            match self.peek() {
+                Some('[') => depth += 1,
+                Some(']') => depth -= 1,
+                Some('>') if depth == 0 => {
+                    self.advance(1);
+                    return Ok(());
+                },
+                Some(_) => {},
+                _ => raise!(self.line, "found an unterminated declaration"),
+            }
+            self.advance(1);
+        }
+    }
+
+    fn skip_until(&mut self, end: &str) -> Result<()> {
+        while !self.starts_with(end) {
+            if self.peek().is_none() {
+                raise!(self.line, "expected `{}`", end);
+            }
+            self.advance(1);
+        }
+        self.advance(end.len());
+        Ok(())
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(c) = self.peek() {
+            if !c.is_whitespace() {
+                break;
+            }
+            self.advance(1);
+        }
+    }
+
+    fn expect(&mut self, expected: char) -> Result<()> {
+        if self.peek() != Some(expected) {
+            raise!(self.line, "expected `{}`", expected);
+        }
+        self.advance(1);
+        Ok(())
+    }
+
+    fn starts_with(&self, prefix: &str) -> bool {
+        let rest = &self.chars[self.position.min(self.chars.len())..];
+        rest.len() >= prefix.len() && rest.iter().zip(prefix.chars()).all(|(&c, p)| c == p)
+    }
+
+    #[inline]
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.position).cloned()
+    }
+
+    fn advance(&mut self, count: usize) {
+        for _ in 0..count {
+            if self.chars.get(self.position) == Some(&'\n') {
+                self.line += 1;
+            }
+            self.position += 1;
+        }
+    }
+}
+
+fn graph(element: &Element, keys: &HashMap<&str, Key>) -> Result<Graph> {
+    if element.find("edgedefault") == Some("undirected") {
+        raise!(element.line, "expected a directed graph");
+    }
+    let (name, id) = title(element.find("id").map(String::from));
+    let mut graph = new_graph(name, id);
+
+    for (name, value, line) in data(element, "graph", keys)? {
+        graph.attributes.insert(name.to_string(), natural(value, name, line)?);
+    }
+
+    let mut index = HashMap::new();
+    for node in element.children.iter().filter(|child| child.name == "node") {
+        let i = graph.tasks.len();
+        if index.insert(node.attribute("id")?, i).is_some() {
+            raise!(node.line, "found a duplicate node `{}`", node.attribute("id")?);
+        }
+        let mut kind = 0;
+        for (name, value, line) in data(node, "node", keys)? {
+            match name {
+                "type" => kind = natural(value, name, line)?,
+                "deadline" => {
+                    let id = graph.deadlines.len();
+                    graph.deadlines.push(new_deadline(id, i, natural(value, name, line)?));
+                },
+                _ => {},
+            }
+        }
+        graph.tasks.push(new_task(i, kind));
+    }
+
+    for edge in element.children.iter().filter(|child| child.name == "edge") {
+        let mut ends = vec![];
+        for name in &["source", "target"] {
+            let node = edge.attribute(name)?;
+            match index.get(node) {
+                Some(&i) => ends.push(i),
+                _ => raise!(edge.line, "found an unknown node `{}`", node),
+            }
+        }
+        if edge.find("directed") == Some("false") {
+            raise!(edge.line, "expected a directed edge");
+        }
+        let mut kind = 0;
+        for (name, value, line) in data(edge, "edge", keys)? {
+            if name == "type" {
+                kind = natural(value, name, line)?;
+            }
+        }
+        let id = graph.arcs.len();
+        graph.arcs.push(new_arc(id, ends[0], ends[1], kind));
+    }
+
+    Ok(graph)
+}
+
+/// Collect the data of an element with numeric keys of its domain, including
+/// the defaults of such keys, as triples of names, values and lines.
+fn data<'a>(element: &'a Element, domain: &str, keys: &HashMap<&str, Key<'a>>)
+            -> Result<Vec<(&'a str, &'a str, usize)>> {
+
+    let mut data = vec![];
+    let mut given = vec![];
+    for child in element.children.iter().filter(|child| child.name == "data") {
+        let id = child.attribute("key")?;
+        let key = match keys.get(id) {
+            Some(key) => key,
+            _ => raise!(child.line, "found an undeclared key `{}`", id),
+        };
+        if key.numeric && (key.domain == domain || key.domain == "all") {
+            given.push(id);
+            data.push((key.name, child.text.trim(), child.line));
+        }
+    }
+    let mut defaults = keys.iter().filter(|&(id, key)| {
+        key.numeric && (key.domain == domain || key.domain == "all") && !given.contains(id)
+    }).filter_map(|(_, key)| key.default.map(|(value, line)| (key.name, value, line)))
+      .collect::<Vec<_>>();
+    defaults.sort();
+    defaults.extend(data);
+    Ok(defaults)
+}
+
+fn decode(text: &str, line: usize) -> Result<String> {
+    let mut result = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('&') {
+        result.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let end = match rest.find(';') {
+            Some(end) => end,
+            _ => raise!(line, "found an unterminated entity"),
+        };
+        let entity = &rest[1..end];
+        let c = match entity {
+            "amp" => Some('&'),
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            _ if entity.starts_with("#x") => {
+                u32::from_str_radix(&entity[2..], 16).ok().and_then(::std::char::from_u32)
+            },
+            _ if entity.starts_with('#') => {
+                entity[1..].parse().ok().and_then(::std::char::from_u32)
+            },
+            _ => None,
+        };
+        match c {
+            Some(c) => result.push(c),
+            _ => raise!(line, "found an unknown entity `&{};`", entity),
+        }
+        rest = &rest[(end + 1)..];
+    }
+    result.push_str(rest);
+    Ok(result)
+}
+
+fn local(name: &str) -> &str {
+    match name.rfind(':') {
+        Some(i) => &name[(i + 1)..],
+        _ => name,
+    }
+}
+
+fn sorted(attributes: &HashMap<String, usize>) -> Vec<(&String, &usize)> {
+    let mut attributes = attributes.iter().collect::<Vec<_>>();
+    attributes.sort();
+    attributes
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    const INPUT: &str = "@HYPERPERIOD 10
+
+@GRAPH 0 {
+    PERIOD 10
+    TASK t0_0 TYPE 3
+    TASK t0_1 TYPE 4
+    TASK t0_2 TYPE 5
+    ARC a0_0 FROM t0_0 TO t0_1 TYPE 0
+    ARC a0_1 FROM t0_0 TO t0_2 TYPE 1
+    HARD_DEADLINE d0_0 ON t0_2 AT 9
+}
+";
+
+    #[test]
+    fn write() {
+        let content = ::parse(INPUT).unwrap();
+        assert_eq!(super::write(&content), "<?xml version=\"1.0\" encoding=\"UTF-8\"?>
+<graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\">
+  <key id=\"graphml_HYPERPERIOD\" for=\"graphml\" attr.name=\"HYPERPERIOD\" attr.type=\"long\"/>
+  <key id=\"graph_PERIOD\" for=\"graph\" attr.name=\"PERIOD\" attr.type=\"long\"/>
+  <key id=\"node_type\" for=\"node\" attr.name=\"type\" attr.type=\"long\"/>
+  <key id=\"node_deadline\" for=\"node\" attr.name=\"deadline\" attr.type=\"long\"/>
+  <key id=\"edge_type\" for=\"edge\" attr.name=\"type\" attr.type=\"long\"/>
+  <data key=\"graphml_HYPERPERIOD\">10</data>
+  <graph id=\"GRAPH_0\" edgedefault=\"directed\">
+    <data key=\"graph_PERIOD\">10</data>
+    <node id=\"t0_0\"><data key=\"node_type\">3</data></node>
+    <node id=\"t0_1\"><data key=\"node_type\">4</data></node>
+    <node id=\"t0_2\"><data key=\"node_type\">5</data><data key=\"node_deadline\">9</data></node>
+    <edge id=\"a0_0\" source=\"t0_0\" target=\"t0_1\"><data key=\"edge_type\">0</data></edge>
+    <edge id=\"a0_1\" source=\"t0_0\" target=\"t0_2\"><data key=\"edge_type\">1</data></edge>
+  </graph>
+</graphml>
+");
+    }
+
+    #[test]
+    fn read() {
+        let content = ::parse(INPUT).unwrap();
+        assert_eq!(super::read(&super::write(&content)).unwrap(), content);
+
+        let content = super::read("<?xml version=\"1.0\"?>
+<!DOCTYPE graphml [ <!ENTITY x \"y\"> ]>
+<graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\"
+         xmlns:y=\"http://www.yworks.com/xml/graphml\">
+  <!-- keys -->
+  <key id=\"d0\" for=\"node\" attr.name=\"type\" attr.type=\"int\"><default>7</default></key>
+  <key id=\"d1\" for=\"edge\" attr.name=\"type\" attr.type=\"int\"/>
+  <key id=\"d2\" for=\"graph\" attr.name=\"label\" attr.type=\"string\"/>
+  <key id=\"d3\" for=\"graph\" attr.name=\"PERIOD\" attr.type=\"double\"/>
+  <graph id=\"G\" edgedefault=\"directed\">
+    <data key=\"d2\"><![CDATA[a <label>]]> &amp; more</data>
+    <data key=\"d3\">12.0</data>
+    <node id=\"n0\"><data key=\"d0\">2</data><y:ShapeNode/></node>
+    <node id=\"n1\"/>
+    <edge source=\"n1\" target=\"n0\"><data key=\"d1\">3</data></edge>
+  </graph>
+</graphml>
+").unwrap();
+        assert!(content.attributes.is_empty());
+        let graph = &content.graphs[0];
+        assert_eq!((&graph.name[..], graph.id), ("G", 0));
+        assert_eq!(graph.attributes.len(), 1);
+        assert_eq!(graph.attributes["PERIOD"], 12);
+        assert_eq!(graph.tasks.iter().map(|task| task.kind).collect::<Vec<_>>(), &[2, 7]);
+        assert_eq!((graph.arcs[0].from, graph.arcs[0].to, graph.arcs[0].kind), (1, 0, 3));
+
+        let content = super::read("<graphml>
+  <key id=\"a\" for=\"graph\" attr.name=\"PERIOD\" attr.type=\"long\"/>
+  <key id=\"b\" for=\"graphml\" attr.name=\"PERIOD\" attr.type=\"long\"/>
+  <key id=\"c\" for=\"graph\" attr.name=\"label\" attr.type=\"string\"/>
+  <key id=\"d\" for=\"node\" attr.name=\"label\" attr.type=\"long\"/>
+  <data key=\"b\">3</data>
+  <graph id=\"G_1\">
+    <data key=\"a\">5</data>
+    <data key=\"c\">hello</data>
+    <node id=\"n\"><data key=\"d\">1</data></node>
+  </graph>
+</graphml>").unwrap();
+        assert_eq!(content.attributes.len(), 1);
+        assert_eq!(content.attributes["PERIOD"], 3);
+        assert_eq!(content.graphs[0].attributes.len(), 1);
+        assert_eq!(content.graphs[0].attributes["PERIOD"], 5);
+
+        let error = super::read("<graphml>\n  <graph edgedefault=\"undirected\"/>\n</graphml>")
+                          .err().unwrap();
+        assert_eq!((error.line, &error.message[..]), (2, "expected a directed graph"));
+        let error = super::read("<graphml>\n<graph>\n<node id=\"a\"/>\n<edge source=\"a\" \
+                                 target=\"b\"/>\n</graph>\n</graphml>").err().unwrap();
+        assert_eq!((error.line, &error.message[..]), (4, "found an unknown node `b`"));
+        let error = super::read("<graphml>\n<graph>\n</graphml>").err().unwrap();
+        assert_eq!((error.line, &error.message[..]), (3, "expected the closing tag of `graph`"));
+    }
+}
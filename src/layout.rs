@@ -0,0 +1,343 @@
+//! Layered drawings of task graphs.
+//!
+//! The layout follows the Sugiyama approach. Tasks are assigned to layers by
+//! the longest paths from the sources, arcs spanning several layers are split
+//! by dummy vertices, the order of the vertices in each layer is chosen by the
+//! barycenter heuristic to reduce the number of crossings, and horizontal
+//! coordinates are chosen to straighten arcs while keeping vertices apart.
+
+use std::cmp::Ordering;
+
+use Graph;
+
+/// A configuration of a layout.
+#[derive(Clone, Copy, Debug)]
+pub struct Config {
+    /// The width of a task.
+    pub node_width: f64,
+    /// The height of a task.
+    pub node_height: f64,
+    /// The horizontal gap between neighboring vertices of a layer.
+    pub node_spacing: f64,
+    /// The vertical gap between neighboring layers.
+    pub layer_spacing: f64,
+    /// The gap between the drawing and its borders.
+    pub margin: f64,
+    /// The number of sweeps of crossing reduction.
+    pub sweeps: usize,
+}
+
+/// A layout of a task graph.
+#[derive(Clone, Debug)]
+pub struct Layout {
+    /// The width of the drawing.
+    pub width: f64,
+    /// The height of the drawing.
+    pub height: f64,
+    /// The placement of each task in the order of the tasks of the graph.
+    pub nodes: Vec<Node>,
+    /// The route of each arc in the order of the arcs of the graph.
+    pub edges: Vec<Edge>,
+}
+
+/// The placement of a task.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Node {
+    /// The ID of the task.
+    pub task: usize,
+    /// The index of the layer.
+    pub layer: usize,
+    /// The horizontal coordinate of the center.
+    pub x: f64,
+    /// The vertical coordinate of the center.
+    pub y: f64,
+    /// The width.
+    pub width: f64,
+    /// The height.
+    pub height: f64,
+}
+
+/// The route of an arc.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Edge {
+    /// The ID of the arc.
+    pub arc: usize,
+    /// The points of a polyline from the bottom of the source task through the
+    /// layers in between to the top of the target task.
+    pub points: Vec<(f64, f64)>,
+}
+
+impl Default for Config {
+    fn default() -> Config {
+        Config {
+            node_width: 80.0,
+            node_height: 40.0,
+            node_spacing: 20.0,
+            layer_spacing: 50.0,
+            margin: 20.0,
+            sweeps: 8,
+        }
+    }
+}
+
+/// Compute a layout of a task graph.
+///
+/// The function returns `None` if the graph is not acyclic.
+pub fn new(graph: &Graph, config: &Config) -> Option<Layout> {
+    let dag = match ::dag::new(graph) {
+        Some(dag) => dag,
+        _ => return None,
+    };
+
+    let count = graph.tasks.len();
+    let mut layers = vec![0; count];
+    for &i in dag.order.iter() {
+        for &(j, _) in dag.successors[i].iter() {
+            layers[j] = layers[j].max(layers[i] + 1);
+        }
+    }
+
+    let mut chains = Vec::with_capacity(graph.arcs.len());
+    for i in 0..count {
+        for &(j, k) in dag.successors[i].iter() {
+            let mut chain = vec![i];
+            for layer in (layers[i] + 1)..layers[j] {
+                chain.push(layers.len());
+                layers.push(layer);
+            }
+            chain.push(j);
+            chains.push((k, chain));
+        }
+    }
+    chains.sort_by_key(|&(k, _)| k);
+
+    let total = layers.len();
+    let mut upper = vec![vec![]; total];
+    let mut lower = vec![vec![]; total];
+    for chain in chains.iter().map(|pair| &pair.1) {
+        for pair in chain.windows(2) {
+            lower[pair[0]].push(pair[1]);
+            upper[pair[1]].push(pair[0]);
+        }
+    }
+
+    let depth = layers.iter().cloned().max().map(|layer| layer + 1).unwrap_or(0);
+    let mut order = vec![vec![]; depth];
+    for &i in dag.order.iter() {
+        order[layers[i]].push(i);
+    }
+    for chain in chains.iter().map(|pair| &pair.1) {
+        for &v in chain[1..(chain.len() - 1)].iter() {
+            order[layers[v]].push(v);
+        }
+    }
+    let order = arrange(order, &upper, &lower, total, config.sweeps);
+
+    let width = |v: usize| if v < count { config.node_width } else { 0.0 };
+    let mut x = vec![0.0; total];
+    for vertices in order.iter() {
+        let mut position = 0.0;
+        for (p, &v) in vertices.iter().enumerate() {
+            if p > 0 {
+                position += (width(vertices[p - 1]) + width(v)) / 2.0 + config.node_spacing;
+            }
+            x[v] = position;
+        }
+    }
+    for _ in 0..config.sweeps {
+        for vertices in order.iter() {
+            let desired = vertices.iter().map(|&v| {
+                let neighbors = upper[v].iter().chain(lower[v].iter()).collect::<Vec<_>>();
+                if neighbors.is_empty() {
+                    return x[v];
+                }
+                neighbors.iter().map(|&&u| x[u]).sum::<f64>() / neighbors.len() as f64
+            }).collect::<Vec<_>>();
+            for (p, &v) in vertices.iter().enumerate() {
+                x[v] = desired[p];
+                if p > 0 {
+                    let u = vertices[p - 1];
+                    let least = x[u] + (width(u) + width(v)) / 2.0 + config.node_spacing;
+                    if x[v] < least {
+                        x[v] = least;
+                    }
+                }
+            }
+            for p in (1..vertices.len()).rev() {
+                let (u, v) = (vertices[p - 1], vertices[p]);
+                let most = x[v] - (width(u) + width(v)) / 2.0 - config.node_spacing;
+                if x[u] > most {
+                    x[u] = most;
+                }
+            }
+        }
+    }
+
+    let left = (0..total).map(|v| x[v] - width(v) / 2.0).fold(0.0, f64::min);
+    let right = (0..total).map(|v| x[v] + width(v) / 2.0).fold(left, f64::max);
+    let shift = config.margin - left;
+    let y = |v: usize| {
+        config.margin + config.node_height / 2.0 +
+            layers[v] as f64 * (config.node_height + config.layer_spacing)
+    };
+
+    let nodes = (0..count).map(|i| Node {
+        task: graph.tasks[i].id,
+        layer: layers[i],
+        x: x[i] + shift,
+        y: y(i),
+        width: config.node_width,
+        height: config.node_height,
+    }).collect();
+    let edges = chains.iter().map(|&(k, ref chain)| {
+        let last = chain.len() - 1;
+        let points = chain.iter().enumerate().map(|(p, &v)| {
+            let offset = match p {
+                0 => config.node_height / 2.0,
+                p if p == last => -config.node_height / 2.0,
+                _ => 0.0,
+            };
+            (x[v] + shift, y(v) + offset)
+        }).collect();
+        Edge { arc: graph.arcs[k].id, points: points }
+    }).collect();
+
+    Some(Layout {
+        width: right - left + 2.0 * config.margin,
+        height: if depth == 0 {
+            2.0 * config.margin
+        } else {
+            depth as f64 * (config.node_height + config.layer_spacing) - config.layer_spacing +
+                2.0 * config.margin
+        },
+        nodes: nodes,
+        edges: edges,
+    })
+}
+
+/// Order the vertices of each layer by the barycenter heuristic and return
+/// the order with the fewest crossings.
+fn arrange(mut order: Vec<Vec<usize>>, upper: &[Vec<usize>], lower: &[Vec<usize>],
+           total: usize, sweeps: usize) -> Vec<Vec<usize>> {
+
+    let mut best = order.clone();
+    let mut fewest = crossings(&order, lower, total);
+    let mut position = vec![0; total];
+    for sweep in 0..sweeps {
+        let downward = sweep % 2 == 0;
+        let layers = order.len();
+        for step in 1..layers {
+            let l = if downward { step } else { layers - 1 - step };
+            let (fixed, neighbors) = if downward {
+                (&order[l - 1], upper)
+            } else {
+                (&order[l + 1], lower)
+            };
+            for (p, &v) in fixed.iter().enumerate() {
+                position[v] = p;
+            }
+            let mut keys = order[l].iter().enumerate().map(|(p, &v)| {
+                let key = if neighbors[v].is_empty() {
+                    p as f64
+                } else {
+                    let sum = neighbors[v].iter().map(|&u| position[u] as f64).sum::<f64>();
+                    sum / neighbors[v].len() as f64
+                };
+                (key, p, v)
+            }).collect::<Vec<_>>();
+            keys.sort_by(|one, other| {
+                one.0.partial_cmp(&other.0).unwrap_or(Ordering::Equal).then(one.1.cmp(&other.1))
+            });
+            order[l] = keys.into_iter().map(|(_, _, v)| v).collect();
+        }
+        let count = crossings(&order, lower, total);
+        if count < fewest {
+            fewest = count;
+            best = order.clone();
+        }
+    }
+    best
+}
+
+/// Count the crossings between the segments of neighboring layers.
+fn crossings(order: &[Vec<usize>], lower: &[Vec<usize>], total: usize) -> usize {
+    let mut position = vec![0; total];
+    for vertices in order.iter() {
+        for (p, &v) in vertices.iter().enumerate() {
+            position[v] = p;
+        }
+    }
+    let mut count = 0;
+    for vertices in order.iter() {
+        let segments = vertices.iter().flat_map(|&v| {
+            lower[v].iter().map(move |&u| (v, u))
+        }).map(|(v, u)| (position[v], position[u])).collect::<Vec<_>>();
+        for (s, one) in segments.iter().enumerate() {
+            for other in segments[(s + 1)..].iter() {
+                if (one.0 < other.0 && one.1 > other.1) || (one.0 > other.0 && one.1 < other.1) {
+                    count += 1;
+                }
+            }
+        }
+    }
+    count
+}
+
+#[cfg(test)]
+mod tests {
+    use content::{new_arc, new_graph, new_task};
+    use super::Config;
+
+    #[test]
+    fn new() {
+        let mut graph = new_graph(String::from("GRAPH"), 0);
+        for i in 0..5 {
+            graph.tasks.push(new_task(i, 0));
+        }
+        graph.arcs.push(new_arc(0, 0, 3, 0));
+        graph.arcs.push(new_arc(1, 1, 2, 0));
+        graph.arcs.push(new_arc(2, 2, 4, 0));
+        graph.arcs.push(new_arc(3, 3, 4, 0));
+        graph.arcs.push(new_arc(4, 0, 4, 0));
+
+        let config = Config::default();
+        let layout = super::new(&graph, &config).unwrap();
+        let layers = layout.nodes.iter().map(|node| node.layer).collect::<Vec<_>>();
+        assert_eq!(layers, &[0, 0, 1, 1, 2]);
+        assert!(layout.nodes[0].x < layout.nodes[1].x);
+        assert!(layout.nodes[3].x < layout.nodes[2].x);
+        assert_eq!(layout.nodes[0].y, layout.nodes[1].y);
+        assert!(layout.nodes[0].y < layout.nodes[2].y);
+
+        for one in layout.nodes.iter() {
+            assert!(one.x - config.node_width / 2.0 >= config.margin - 1e-9);
+            assert!(one.x + config.node_width / 2.0 <= layout.width - config.margin + 1e-9);
+            for other in layout.nodes.iter().filter(|other| other.task > one.task) {
+                if one.layer == other.layer {
+                    assert!((one.x - other.x).abs() >= config.node_width + config.node_spacing -
+                            1e-9);
+                }
+            }
+        }
+
+        assert_eq!(layout.edges.len(), 5);
+        assert_eq!(layout.edges[4].arc, 4);
+        let points = &layout.edges[4].points;
+        assert_eq!(points.len(), 3);
+        assert_eq!(points[0], (layout.nodes[0].x, layout.nodes[0].y + config.node_height / 2.0));
+        assert_eq!(points[2], (layout.nodes[4].x, layout.nodes[4].y - config.node_height / 2.0));
+        assert_eq!(layout.height, 3.0 * 40.0 + 2.0 * 50.0 + 2.0 * 20.0);
+
+        graph.arcs.push(new_arc(5, 4, 0, 0));
+        assert!(super::new(&graph, &config).is_none());
+    }
+
+    #[test]
+    fn crossings() {
+        let order = vec![vec![0, 1], vec![2, 3]];
+        let lower = vec![vec![3], vec![2], vec![], vec![]];
+        assert_eq!(super::crossings(&order, &lower, 4), 1);
+        let order = super::arrange(order, &[vec![], vec![], vec![1], vec![0]], &lower, 4, 2);
+        assert_eq!(order, &[vec![0, 1], vec![3, 2]]);
+    }
+}
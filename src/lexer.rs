@@ -0,0 +1,280 @@
+use std::iter::Peekable;
+use std::str::Chars;
+
+use {Error, Result};
+
+/// A token of a graph description.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Token {
+    /// An identifier or a number.
+    Word(String),
+    /// A double-quoted string with escapes resolved.
+    Text(String),
+    /// An edge operator, which is either `->` or `--`.
+    Arrow(&'static str),
+    /// Any other character.
+    Symbol(char),
+}
+
+/// A tokenizer shared by the readers of graph descriptions.
+///
+/// Whitespace, C-style comments and lines starting with `#` are skipped.
+pub struct Lexer<'a> {
+    cursor: Peekable<Chars<'a>>,
+    /// The line of the last token returned or peeked.
+    pub line: usize,
+    current: usize,
+    fresh: bool,
+    peeked: Option<Option<Token>>,
+}
+
+impl<'a> Lexer<'a> {
+    /// Create a tokenizer.
+    pub fn new(input: &'a str) -> Lexer<'a> {
+        Lexer {
+            cursor: input.chars().peekable(),
+            line: 1,
+            current: 1,
+            fresh: true,
+            peeked: None,
+        }
+    }
+
+    /// Return the next token without consuming it.
+    pub fn peek(&mut self) -> Result<Option<&Token>> {
+        if self.peeked.is_none() {
+            let token = self.read()?;
+            self.peeked = Some(token);
+        }
+        Ok(self.peeked.as_ref().unwrap().as_ref())
+    }
+
+    /// Return the next token.
+    pub fn next(&mut self) -> Result<Option<Token>> {
+        match self.peeked.take() {
+            Some(token) => Ok(token),
+            _ => self.read(),
+        }
+    }
+
+    /// Consume the next token if it is a particular symbol.
+    pub fn accept(&mut self, symbol: char) -> Result<bool> {
+        if self.peek()? == Some(&Token::Symbol(symbol)) {
+            self.next()?;
+            return Ok(true);
+        }
+        Ok(false)
+    }
+
+    /// Consume the next token, which has to be a particular symbol.
+    pub fn expect(&mut self, symbol: char) -> Result<()> {
+        if !self.accept(symbol)? {
+            return Err(self.error(format!("expected `{}`", symbol)));
+        }
+        Ok(())
+    }
+
+    /// Consume the next token, which has to be a word or a string.
+    pub fn value(&mut self) -> Result<String> {
+        match self.next()? {
+            Some(Token::Word(value)) | Some(Token::Text(value)) => Ok(value),
+            _ => Err(self.error(String::from("expected an identifier or a string"))),
+        }
+    }
+
+    /// Create an error on the current line.
+    pub fn error(&self, message: String) -> Error {
+        Error { line: self.line, message: message }
+    }
+
+    fn read(&mut self) -> Result<Option<Token>> {
+        self.skip()?;
+        self.line = self.current;
+        self.fresh = false;
+        let c = match self.cursor.next() {
+            Some(c) => c,
+            _ => return Ok(None),
+        };
+        match c {
+            '"' => {
+                let mut value = String::new();
+                loop {
+                    match self.cursor.next() {
+                        Some('"') => break,
+                        Some('\\') => match self.cursor.next() {
+                            Some('"') => value.push('"'),
+                            Some('\\') => value.push('\\'),
+                            Some('\n') => self.current += 1,
+                            Some(c) => {
+                                value.push('\\');
+                                value.push(c);
+                            },
+                            _ => break,
+                        },
+                        Some(c) => {
+                            if c == '\n' {
+                                self.current += 1;
+                            }
+                            value.push(c);
+                        },
+                        _ => {
+                            return Err(self.error(String::from("found an unterminated string")));
+                        },
+                    }
+                }
+                Ok(Some(Token::Text(value)))
+            },
+            '-' if self.cursor.peek() == Some(&'>') => {
+                self.cursor.next();
+                Ok(Some(Token::Arrow("->")))
+            },
+            '-' if self.cursor.peek() == Some(&'-') => {
+                self.cursor.next();
+                Ok(Some(Token::Arrow("--")))
+            },
+            c if word(c) || c == '-' && self.cursor.peek().cloned().map(word) == Some(true) => {
+                let mut value = c.to_string();
+                while let Some(&c) = self.cursor.peek() {
+                    if !word(c) {
+                        break;
+                    }
+                    value.push(c);
+                    self.cursor.next();
+                }
+                Ok(Some(Token::Word(value)))
+            },
+            c => Ok(Some(Token::Symbol(c))),
+        }
+    }
+
+    fn skip(&mut self) -> Result<()> {
+        loop {
+            match self.cursor.peek().cloned() {
+                Some('\n') => {
+                    self.cursor.next();
+                    self.current += 1;
+                    self.fresh = true;
+                    continue;
+                },
+                Some(c) if c.is_whitespace() => {
+                    self.cursor.next();
+                    continue;
+                },
+                Some('#') if self.fresh => {
+                    for c in self.cursor.by_ref() {
+                        if c == '\n' {
+                            self.current += 1;
+                            break;
+                        }
+                    }
+                    continue;
+                },
+                Some('/') => {
+                    let mut lookahead = self.cursor.clone();
+                    lookahead.next();
+                    match lookahead.peek() {
+                        Some(&'/') => {
+                            for c in self.cursor.by_ref() {
+                                if c == '\n' {
+                                    self.current += 1;
+                                    break;
+                                }
+                            }
+                            self.fresh = true;
+                            continue;
+                        },
+                        Some(&'*') => {
+                            self.cursor.next();
+                            self.cursor.next();
+                            let mut previous = ' ';
+                            loop {
+                                match self.cursor.next() {
+                                    Some('/') if previous == '*' => break,
+                                    Some(c) => {
+                                        if c == '\n' {
+                                            self.current += 1;
+                                        }
+                                        previous = c;
+                                    },
+                                    _ => {
+                                        self.line = self.current;
+                                        let message = String::from("found an unterminated comment");
+                                        return Err(self.error(message));
+                                    },
+                                }
+                            }
+                            continue;
+                        },
+                        _ => {},
+                    }
+                },
+                _ => {},
+            }
+            return Ok(());
+        }
+    }
+}
+
+/// Attributes as triples of names, values and lines.
+pub type Attributes = Vec<(String, String, usize)>;
+
+/// Find the last occurrence of an attribute.
+pub fn find<'a>(attributes: &'a Attributes, name: &str) -> Option<&'a (String, String, usize)> {
+    attributes.iter().rev().find(|attribute| attribute.0 == name)
+}
+
+/// Split a title such as `GRAPH_0` into the name and ID of a graph.
+pub fn title(title: Option<String>) -> (String, usize) {
+    match title {
+        Some(title) => match title.rfind('_').map(|i| (&title[..i], title[(i + 1)..].parse())) {
+            Some((prefix, Ok(id))) if !prefix.is_empty() => (prefix.to_string(), id),
+            _ => (title, 0),
+        },
+        _ => (String::from("TASK_GRAPH"), 0),
+    }
+}
+
+/// Parse the value of an attribute as a natural number.
+pub fn natural(value: &str, name: &str, line: usize) -> Result<usize> {
+    match value.parse::<f64>() {
+        Ok(number) if number >= 0.0 && number.fract() == 0.0 => Ok(number as usize),
+        _ => raise!(line, "failed to parse the value `{}` of `{}`", value, name),
+    }
+}
+
+/// Escape a string to be enclosed in double quotes.
+pub fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+fn word(c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || c == '.'
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Lexer, Token};
+
+    #[test]
+    fn next() {
+        let mut lexer = Lexer::new("# comment\na -> \"b \\\"c\\\"\" // comment\n/* a\nb */ [x=-1.5];");
+        let mut tokens = vec![];
+        while let Some(token) = lexer.next().unwrap() {
+            tokens.push((lexer.line, token));
+        }
+        assert_eq!(tokens, vec![
+            (2, Token::Word(String::from("a"))),
+            (2, Token::Arrow("->")),
+            (2, Token::Text(String::from("b \"c\""))),
+            (4, Token::Symbol('[')),
+            (4, Token::Word(String::from("x"))),
+            (4, Token::Symbol('=')),
+            (4, Token::Word(String::from("-1.5"))),
+            (4, Token::Symbol(']')),
+            (4, Token::Symbol(';')),
+        ]);
+
+        let error = Lexer::new("\n\"abc").next().unwrap_err();
+        assert_eq!(error.line, 2);
+    }
+}
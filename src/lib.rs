@@ -1,7 +1,42 @@
 //! A parser for the [TGFF][1] (Task Graphs For Free) format.
 //!
+//! ## Serialization
+//!
+//! With the `serde` feature enabled, `Content` and the types it consists of
+//! implement `Serialize` and `Deserialize`. Each structure is represented by a
+//! map whose keys are the names of its fields, and attributes are represented
+//! by maps sorted by name. In JSON, for instance, a file with a single task
+//! graph looks as follows:
+//!
+//! ```json
+//! {
+//!   "attributes": {"HYPERPERIOD": 8},
+//!   "graphs": [{
+//!     "name": "GRAPH",
+//!     "id": 0,
+//!     "attributes": {"PERIOD": 8},
+//!     "tasks": [{"id": 0, "kind": 1}, {"id": 1, "kind": 2}],
+//!     "arcs": [{"id": 0, "from": 0, "to": 1, "kind": 0}],
+//!     "deadlines": [{"id": 0, "on": 1, "at": 8}]
+//!   }],
+//!   "tables": [{
+//!     "name": "CORE",
+//!     "id": 0,
+//!     "attributes": {"price": 10.5042},
+//!     "columns": [{"name": "type", "data": [1.0, 2.0]}]
+//!   }]
+//! }
+//! ```
+//!
+//! The feature also provides `from_str` and `to_string`, which read and write
+//! TGFF files directly as any types of the above shape.
+//!
 //! [1]: http://ziyang.eecs.umich.edu/~dickrp/tgff/
 
+#[cfg(feature = "serde")]
+#[macro_use]
+extern crate serde;
+
 use std::fmt;
 use std::iter::Peekable;
 use std::str::CharIndices;
@@ -10,7 +45,52 @@ pub use content::Content;
 pub use content::{Graph, Task, Arc, Deadline};
 pub use content::{Table, Column};
 
+#[cfg(feature = "serde")]
+pub use format::{from_str, to_string};
+
+macro_rules! raise(
+    ($line:expr, $($arg:tt)*) => (
+        return Err(Error { line: $line, message: format!($($arg)*) })
+    );
+);
+
+macro_rules! some(
+    ($parser:expr, $result:expr, $($arg:tt)*) => (
+        match $result {
+            Some(result) => result,
+            _ => raise!($parser.line, $($arg)*),
+        }
+    );
+);
+
+pub mod communication;
+pub mod csv;
+pub mod dot;
+pub mod energy;
+pub mod generator;
+pub mod genetic;
+pub mod graphml;
+pub mod layout;
+pub mod mapping;
+pub mod milp;
+pub mod optimization;
+pub mod options;
+pub mod pareto;
+pub mod platform;
+pub mod power;
+pub mod schedulability;
+pub mod schedule;
+pub mod shape;
+pub mod svg;
+pub mod thermal;
+pub mod vcg;
+
 mod content;
+mod dag;
+#[cfg(feature = "serde")]
+mod format;
+mod lexer;
+mod random;
 
 static READ_CAPACITY: usize = 20;
 
@@ -39,21 +119,6 @@ pub fn parse(input: &str) -> Result<Content> {
     Parser::new(input).process()
 }
 
-macro_rules! raise(
-    ($parser:expr, $($arg:tt)*) => (
-        return Err(Error { line: $parser.line, message: format!($($arg)*) });
-    );
-);
-
-macro_rules! some(
-    ($parser:expr, $result:expr, $($arg:tt)*) => (
-        match $result {
-            Some(result) => result,
-            _ => raise!($parser, $($arg)*),
-        }
-    );
-);
-
 impl fmt::Debug for Error {
     fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
         write!(formatter, "{} on line {}", self.message, self.line)
@@ -67,6 +132,9 @@ impl fmt::Display for Error {
     }
 }
 
+impl std::error::Error for Error {
+}
+
 impl<'a> Parser<'a> {
     fn new(input: &'a str) -> Parser<'a> {
         Parser {
@@ -80,7 +148,7 @@ impl<'a> Parser<'a> {
         loop {
             match self.peek() {
                 Some('@') => try!(self.process_at()),
-                Some(_) => raise!(self, "found an unknown statement"),
+                Some(_) => raise!(self.line, "found an unknown statement"),
                 _ => break,
             }
         }
@@ -158,20 +226,24 @@ impl<'a> Parser<'a> {
     fn process_table(&mut self, name: String, id: usize) -> Result<()> {
         let mut table = content::new_table(name, id);
 
-        try!(self.skip_char('#'));
+        if let Some('-') = self.cursor.clone().nth(1).map(|(_, c)| c) {
+            try!(self.skip_comment());
+        } else {
+            try!(self.skip_char('#'));
 
-        let mut names = vec![];
-        loop {
-            match self.read_token() {
-                Some(token) => names.push(token),
-                _ => break,
+            let mut names = vec![];
+            loop {
+                match self.read_token() {
+                    Some(token) => names.push(token),
+                    _ => break,
+                }
+            }
+            for name in names.into_iter() {
+                table.attributes.insert(name, try!(self.get_real()));
             }
-        }
-        for name in names.into_iter() {
-            table.attributes.insert(name, try!(self.get_real()));
-        }
 
-        try!(self.skip_comment());
+            try!(self.skip_comment());
+        }
         try!(self.skip_char('#'));
 
         loop {
@@ -230,13 +302,13 @@ impl<'a> Parser<'a> {
             },
             _ => {},
         }
-        raise!(self, "expected `{}`", expected);
+        raise!(self.line, "expected `{}`", expected);
     }
 
     fn skip_str(&mut self, expected: &str) -> Result<()> {
         let (len, vec) = (expected.len(), expected.chars().collect::<Vec<_>>());
         if self.skip(&|i, c| i < len && c == vec[i]) != len {
-            raise!(self, "expected `{}`", expected);
+            raise!(self.line, "expected `{}`", expected);
         }
         self.skip_void();
         Ok(())
@@ -249,7 +321,7 @@ impl<'a> Parser<'a> {
 
     fn skip_comment(&mut self) -> Result<()> {
         if self.skip(&|i, c| i == 0 && c == '#' || (i > 0) && c == '-') < 2 {
-            raise!(self, "expected a comment line");
+            raise!(self.line, "expected a comment line");
         }
         self.skip_void();
         Ok(())
@@ -335,28 +407,28 @@ impl<'a> Parser<'a> {
     fn get_token(&mut self) -> Result<String> {
         match self.read_token() {
             Some(token) => Ok(token),
-            _ => raise!(self, "expected a token"),
+            _ => raise!(self.line, "expected a token"),
         }
     }
 
     fn get_id(&mut self) -> Result<usize> {
         match self.read_id() {
             Some(id) => Ok(id),
-            _ => raise!(self, "expected an id"),
+            _ => raise!(self.line, "expected an id"),
         }
     }
 
     fn get_natural(&mut self) -> Result<usize> {
         match self.read_natural() {
             Some(number) => Ok(number),
-            _ => raise!(self, "expected a natural number"),
+            _ => raise!(self.line, "expected a natural number"),
         }
     }
 
     fn get_real(&mut self) -> Result<f64> {
         match self.read_real() {
             Some(number) => Ok(number),
-            _ => raise!(self, "expected a real number"),
+            _ => raise!(self.line, "expected a real number"),
         }
     }
 }
@@ -434,6 +506,18 @@ mod tests {
         assert_eq!(table.columns[1].name, "baz".to_string());
         assert_eq!(table.columns[0].data, vec![1.0, 3.0]);
         assert_eq!(table.columns[1].data, vec![2.0, 4.0]);
+
+        let mut parser = parser!("#--\n# bar\n1 2 ");
+        assert!(parser.process_table(String::from("T"), 0).is_ok());
+        let ref table = parser.content.tables[0];
+        assert!(table.attributes.is_empty());
+        assert_eq!(table.columns[0].data, vec![1.0, 2.0]);
+        assert!(table.to_string().starts_with("@T 0 {\n#---"));
+        assert_eq!(&::parse(&table.to_string()).unwrap().tables[0], table);
+
+        let mut table = table.clone();
+        table.columns.push(::content::new_column(String::from("baz")));
+        assert!(table.to_string().ends_with("# bar baz\n  1\n  2\n}\n"));
     }
 
     #[test]
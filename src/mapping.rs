@@ -0,0 +1,78 @@
+//! Mappings of tasks onto processing elements.
+
+use std::cmp::Ordering;
+
+use schedule::Schedule;
+
+/// A mapping of the tasks of a graph onto processing elements.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Mapping {
+    /// The assignment of each task.
+    pub assignments: Vec<Assignment>,
+}
+
+/// The assignment of a task to a processing element.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Assignment {
+    /// The ID of the task.
+    pub task: usize,
+    /// The index of the processing element.
+    pub element: usize,
+    /// The version of the task type.
+    pub version: usize,
+    /// The priority of the task, which is higher for smaller values.
+    pub priority: usize,
+}
+
+impl Mapping {
+    /// Find the assignment of a task given its ID.
+    pub fn find(&self, task: usize) -> Option<&Assignment> {
+        self.assignments.iter().find(|assignment| assignment.task == task)
+    }
+}
+
+impl<'a> From<&'a Schedule> for Mapping {
+    /// Extract the mapping of a schedule.
+    ///
+    /// Priorities are assigned in the order of the start times of the tasks.
+    fn from(schedule: &'a Schedule) -> Mapping {
+        let mut order = (0..schedule.entries.len()).collect::<Vec<_>>();
+        order.sort_by(|&i, &j| {
+            let (one, other) = (&schedule.entries[i], &schedule.entries[j]);
+            one.start.partial_cmp(&other.start).unwrap_or(Ordering::Equal)
+        });
+        let mut priorities = vec![0; order.len()];
+        for (priority, &i) in order.iter().enumerate() {
+            priorities[i] = priority;
+        }
+        Mapping {
+            assignments: schedule.entries.iter().zip(priorities).map(|(entry, priority)| {
+                Assignment {
+                    task: entry.task,
+                    element: entry.element,
+                    version: entry.version,
+                    priority: priority,
+                }
+            }).collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use schedule::{Entry, Schedule};
+    use super::Mapping;
+
+    #[test]
+    fn from() {
+        let schedule = Schedule { entries: vec![
+            Entry { task: 0, element: 1, version: 0, start: 2.0, finish: 3.0 },
+            Entry { task: 1, element: 0, version: 2, start: 0.0, finish: 1.0 },
+            Entry { task: 2, element: 0, version: 0, start: 1.0, finish: 3.0 },
+        ]};
+        let mapping = Mapping::from(&schedule);
+        assert_eq!(mapping.assignments.iter().map(|assignment| assignment.priority)
+                                             .collect::<Vec<_>>(), vec![2, 0, 1]);
+        assert_eq!(mapping.find(1).unwrap().version, 2);
+    }
+}
@@ -0,0 +1,484 @@
+//! Mixed-integer linear programming formulations of mapping and scheduling.
+//!
+//! The formulation is disjunctive. The binary variable `x_i_j_v` is one when
+//! the task with ID `i` is executed by the processing element `j` using the
+//! version `v`, the continuous variables `s_i` and `f_i` are the start and
+//! finish times of the task, and `z_k` is one when the tasks connected by the
+//! arc with ID `k` are executed by distinct processing elements. The binary
+//! variable `o_i_l` orders two tasks that share a processing element and are
+//! not ordered by the graph itself, and `C` is the makespan. Models are
+//! written in the CPLEX LP format.
+
+use std::collections::HashMap;
+use std::fmt::Write;
+
+use {Error, Graph, Result};
+use communication::Communication;
+use energy;
+use platform::Platform;
+use schedule::{Entry, Schedule};
+
+/// A configuration of a model.
+#[derive(Clone, Debug, Default)]
+pub struct Config {
+    /// The objective.
+    pub objective: Objective,
+    /// The constant used to relax disjunctive constraints, which is computed
+    /// from the execution and transfer times if not given.
+    pub horizon: Option<f64>,
+}
+
+/// An objective of a model.
+#[derive(Clone, Debug)]
+pub enum Objective {
+    /// Minimize the finish time of the last task.
+    Makespan,
+    /// Minimize the dynamic energy of the tasks and the energy of
+    /// communication for a single instance of the graph.
+    Energy(energy::Config),
+}
+
+impl Default for Objective {
+    #[inline]
+    fn default() -> Objective {
+        Objective::Makespan
+    }
+}
+
+const WIDTH: usize = 72;
+
+/// Write a model of mapping and scheduling a task graph onto a platform.
+///
+/// The graph is released at time zero, and its hard deadlines are imposed on
+/// the finish times of the corresponding tasks. The function returns `None`
+/// if the graph is not acyclic, if some task cannot be executed by any
+/// processing element, or if the dynamic power needed by the energy objective
+/// is unknown.
+pub fn write(graph: &Graph, platform: &Platform, communication: &Communication,
+             config: &Config) -> Option<String> {
+
+    let dag = match ::dag::new(graph) {
+        Some(dag) => dag,
+        _ => return None,
+    };
+    let count = graph.tasks.len();
+
+    let mut choices = vec![vec![]; count];
+    for (i, task) in graph.tasks.iter().enumerate() {
+        for (j, element) in platform.elements.iter().enumerate() {
+            for version in element.versions(task.kind) {
+                if let Some(time) = element.execution_time(task.kind, version) {
+                    choices[i].push((j, version, time));
+                }
+            }
+        }
+        if choices[i].is_empty() {
+            return None;
+        }
+    }
+
+    let mut reachable = vec![vec![false; count]; count];
+    for &i in dag.order.iter().rev() {
+        for &(j, _) in dag.successors[i].iter() {
+            let successor = reachable[j].clone();
+            reachable[i][j] = true;
+            for (one, &other) in reachable[i].iter_mut().zip(successor.iter()) {
+                *one = *one || other;
+            }
+        }
+    }
+
+    let horizon = match config.horizon {
+        Some(horizon) => horizon,
+        _ => {
+            let tasks = choices.iter().map(|choices| {
+                choices.iter().fold(0.0, |longest: f64, &(_, _, time)| longest.max(time))
+            }).sum::<f64>();
+            graph.arcs.iter().fold(tasks, |sum, arc| sum + communication.time(arc.kind))
+        },
+    };
+
+    let id = |i: usize| graph.tasks[i].id;
+    let assigned = |i: usize, j: usize, factor: f64| {
+        choices[i].iter().filter(|&&(other, _, _)| other == j).map(|&(_, version, _)| {
+            (factor, format!("x_{}_{}_{}", id(i), j, version))
+        }).collect::<Vec<_>>()
+    };
+
+    let mut output = String::new();
+    writeln!(output, "\\ Graph {} {}", graph.name, graph.id).unwrap();
+
+    let objective = match config.objective {
+        Objective::Makespan => vec![(1.0, String::from("C"))],
+        Objective::Energy(ref energy) => {
+            let mut terms = vec![];
+            for (i, task) in graph.tasks.iter().enumerate() {
+                for &(j, version, time) in choices[i].iter() {
+                    let power = match platform.elements[j].value(&energy.dynamic_power,
+                                                                 task.kind, version) {
+                        Some(power) => power,
+                        _ => return None,
+                    };
+                    terms.push((power * time, format!("x_{}_{}_{}", task.id, j, version)));
+                }
+            }
+            if energy.transfer_energy != 0.0 {
+                for arc in graph.arcs.iter() {
+                    let factor = energy.transfer_energy * communication.volume(arc.kind);
+                    terms.push((factor, format!("z_{}", arc.id)));
+                }
+            }
+            terms
+        },
+    };
+    writeln!(output, "Minimize").unwrap();
+    constraint(&mut output, "cost", &objective, None);
+
+    writeln!(output, "Subject To").unwrap();
+    for (i, options) in choices.iter().enumerate() {
+        let terms = options.iter().map(|&(j, version, _)| {
+            (1.0, format!("x_{}_{}_{}", id(i), j, version))
+        }).collect::<Vec<_>>();
+        constraint(&mut output, &format!("assign_{}", id(i)), &terms, Some(("=", 1.0)));
+
+        let mut terms = vec![(1.0, format!("f_{}", id(i))), (-1.0, format!("s_{}", id(i)))];
+        for &(j, version, time) in options.iter() {
+            terms.push((-time, format!("x_{}_{}_{}", id(i), j, version)));
+        }
+        constraint(&mut output, &format!("finish_{}", id(i)), &terms, Some(("=", 0.0)));
+
+        let terms = vec![(1.0, String::from("C")), (-1.0, format!("f_{}", id(i)))];
+        constraint(&mut output, &format!("span_{}", id(i)), &terms, Some((">=", 0.0)));
+    }
+
+    for (i, successors) in dag.successors.iter().enumerate() {
+        for &(l, k) in successors.iter() {
+            let arc = &graph.arcs[k];
+            for j in 0..platform.len() {
+                let mut terms = vec![(1.0, format!("z_{}", arc.id))];
+                terms.extend(assigned(i, j, -1.0));
+                if terms.len() == 1 {
+                    continue;
+                }
+                terms.extend(assigned(l, j, 1.0));
+                constraint(&mut output, &format!("split_{}_{}", arc.id, j), &terms,
+                           Some((">=", 0.0)));
+            }
+            let terms = vec![
+                (1.0, format!("s_{}", id(l))),
+                (-1.0, format!("f_{}", id(i))),
+                (-communication.time(arc.kind), format!("z_{}", arc.id)),
+            ];
+            constraint(&mut output, &format!("precede_{}", arc.id), &terms, Some((">=", 0.0)));
+        }
+    }
+
+    let related = |i: usize, l: usize| reachable[i][l] || reachable[l][i];
+    let mut orders = vec![];
+    for i in 0..count {
+        for l in (i + 1)..count {
+            if related(i, l) {
+                continue;
+            }
+            let order = format!("o_{}_{}", id(i), id(l));
+            let mut shared = false;
+            for j in 0..platform.len() {
+                let (one, other) = (assigned(i, j, -horizon), assigned(l, j, -horizon));
+                if one.is_empty() || other.is_empty() {
+                    continue;
+                }
+                shared = true;
+
+                let mut terms = vec![
+                    (1.0, format!("s_{}", id(l))),
+                    (-1.0, format!("f_{}", id(i))),
+                    (-horizon, order.clone()),
+                ];
+                terms.extend(one.iter().cloned());
+                terms.extend(other.iter().cloned());
+                constraint(&mut output, &format!("before_{}_{}_{}", id(i), id(l), j), &terms,
+                           Some((">=", -3.0 * horizon)));
+
+                let mut terms = vec![
+                    (1.0, format!("s_{}", id(i))),
+                    (-1.0, format!("f_{}", id(l))),
+                    (horizon, order.clone()),
+                ];
+                terms.extend(one);
+                terms.extend(other);
+                constraint(&mut output, &format!("after_{}_{}_{}", id(i), id(l), j), &terms,
+                           Some((">=", -2.0 * horizon)));
+            }
+            if shared {
+                orders.push(order);
+            }
+        }
+    }
+
+    for deadline in graph.deadlines.iter() {
+        if graph.tasks.iter().any(|task| task.id == deadline.on) {
+            let terms = vec![(1.0, format!("f_{}", deadline.on))];
+            constraint(&mut output, &format!("deadline_{}", deadline.id), &terms,
+                       Some(("<=", deadline.at as f64)));
+        }
+    }
+
+    writeln!(output, "Binary").unwrap();
+    let mut binaries = vec![];
+    for (i, options) in choices.iter().enumerate() {
+        for &(j, version, _) in options.iter() {
+            binaries.push(format!("x_{}_{}_{}", id(i), j, version));
+        }
+    }
+    binaries.extend(graph.arcs.iter().map(|arc| format!("z_{}", arc.id)));
+    binaries.extend(orders);
+    for binary in binaries {
+        writeln!(output, " {}", binary).unwrap();
+    }
+    writeln!(output, "End").unwrap();
+
+    Some(output)
+}
+
+/// Read a solution of a model into a schedule.
+///
+/// The solution can be given either as lines containing the name of a
+/// variable followed by its value, possibly preceded by an index and followed
+/// by other columns, or as an XML solution file written by CPLEX. Variables
+/// that are absent are assumed to be zero. The finish time of a task is its
+/// start time plus its execution time. Errors about a task that is not
+/// assigned are reported on line zero, and other errors about an assignment are
+/// reported on the line of the corresponding variable.
+pub fn read(input: &str, graph: &Graph, platform: &Platform) -> Result<Schedule> {
+    let mut assignments: HashMap<usize, (usize, usize, usize)> = HashMap::new();
+    let mut starts = HashMap::new();
+
+    let mut line = 0;
+    for text in input.lines() {
+        line += 1;
+        let (name, value) = match variable(text) {
+            Some(variable) => variable,
+            _ => continue,
+        };
+        let value = match value.parse::<f64>() {
+            Ok(value) => value,
+            _ => raise!(line, "failed to parse the value of {}", name),
+        };
+        let indices = match name[2..].split('_').map(|index| index.parse::<usize>())
+                                              .collect::<::std::result::Result<Vec<_>, _>>() {
+            Ok(indices) => indices,
+            _ => continue,
+        };
+        if name.starts_with("s_") && indices.len() == 1 {
+            starts.insert(indices[0], value);
+        } else if name.starts_with("x_") && indices.len() == 3 && value > 0.5 &&
+                  assignments.insert(indices[0], (indices[1], indices[2], line)).is_some() {
+            raise!(line, "the task {} is assigned more than once", indices[0]);
+        }
+    }
+
+    let mut entries = Vec::with_capacity(graph.tasks.len());
+    for task in graph.tasks.iter() {
+        let (element, version, line) = match assignments.get(&task.id) {
+            Some(&assignment) => assignment,
+            _ => raise!(0, "the task {} is not assigned", task.id),
+        };
+        let time = platform.elements.get(element).and_then(|element| {
+            element.execution_time(task.kind, version)
+        });
+        let time = match time {
+            Some(time) => time,
+            _ => raise!(line, "the task {} cannot be executed by the processing element {} \
+                               using the version {}", task.id, element, version),
+        };
+        let start = starts.get(&task.id).cloned().unwrap_or(0.0);
+        entries.push(Entry {
+            task: task.id,
+            element: element,
+            version: version,
+            start: start,
+            finish: start + time,
+        });
+    }
+
+    Ok(Schedule { entries: entries })
+}
+
+fn constraint(output: &mut String, name: &str, terms: &[(f64, String)],
+              bound: Option<(&str, f64)>) {
+
+    let mut current = format!(" {}:", name);
+    for (k, &(factor, ref variable)) in terms.iter().enumerate() {
+        let sign = if factor < 0.0 { " -" } else if k > 0 { " +" } else { "" };
+        let term = if factor.abs() == 1.0 {
+            format!("{} {}", sign, variable)
+        } else {
+            format!("{} {} {}", sign, factor.abs(), variable)
+        };
+        if current.len() + term.len() > WIDTH {
+            writeln!(output, "{}", current).unwrap();
+            current = String::from(" ");
+        }
+        current.push_str(&term);
+    }
+    if terms.is_empty() {
+        current.push_str(" 0");
+    }
+    if let Some((sense, value)) = bound {
+        write!(current, " {} {}", sense, value).unwrap();
+    }
+    writeln!(output, "{}", current).unwrap();
+}
+
+fn variable(text: &str) -> Option<(&str, &str)> {
+    let known = |name: &str| name.starts_with("x_") || name.starts_with("s_");
+    if text.contains("<variable ") {
+        return match (attribute(text, "name"), attribute(text, "value")) {
+            (Some(name), Some(value)) if known(name) => Some((name, value)),
+            _ => None,
+        };
+    }
+    if text.trim_start().starts_with('#') {
+        return None;
+    }
+    let mut tokens = text.split_whitespace();
+    while let Some(token) = tokens.next() {
+        if known(token) {
+            return tokens.next().map(|value| (token, value));
+        }
+    }
+    None
+}
+
+fn attribute<'a>(text: &'a str, name: &str) -> Option<&'a str> {
+    let prefix = format!(" {}=\"", name);
+    text.find(&prefix).and_then(|start| {
+        let rest = &text[(start + prefix.len())..];
+        rest.find('"').map(|end| &rest[..end])
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use communication::Communication;
+    use energy;
+    use platform::Platform;
+    use schedule;
+    use super::{Config, Objective};
+
+    const INPUT: &str = "@GRAPH 0 {
+    TASK t0_0 TYPE 0
+    TASK t0_1 TYPE 1
+    TASK t0_2 TYPE 1
+    ARC a0_0 FROM t0_0 TO t0_1 TYPE 0
+    HARD_DEADLINE d0_0 ON t0_2 AT 10
+}
+@CORE 0 {
+# price
+  1
+#----
+# type version dynamic_power execution_time
+  0    0       1             2
+  1    0       1             3
+}
+@CORE 1 {
+# price
+  2
+#----
+# type version dynamic_power execution_time
+  1    0       3             1
+  1    1       2             2
+}
+";
+
+    #[test]
+    fn write() {
+        let content = ::parse(INPUT).unwrap();
+        let platform = Platform::new(&content, "CORE");
+        let communication = Communication::new(0.5);
+        let graph = &content.graphs[0];
+
+        let model = super::write(graph, &platform, &communication, &Config::default()).unwrap();
+        let lines = model.lines().collect::<Vec<_>>();
+        assert_eq!(&lines[..4], &["\\ Graph GRAPH 0", "Minimize", " cost: C", "Subject To"]);
+        assert!(lines.contains(&" assign_1: x_1_0_0 + x_1_1_0 + x_1_1_1 = 1"));
+        assert!(lines.contains(&" finish_0: f_0 - s_0 - 2 x_0_0_0 = 0"));
+        assert!(lines.contains(&" split_0_0: z_0 - x_0_0_0 + x_1_0_0 >= 0"));
+        assert!(lines.contains(&" precede_0: s_1 - f_0 - 2 z_0 >= 0"));
+        assert!(lines.contains(&" deadline_0: f_2 <= 10"));
+        assert!(lines.contains(&" o_0_2"));
+        assert!(!lines.contains(&" o_0_1"));
+        assert!(lines.iter().all(|line| line.len() <= super::WIDTH));
+        assert_eq!(lines.last(), Some(&"End"));
+
+        let config = Config {
+            objective: Objective::Energy(energy::Config {
+                transfer_energy: 0.25,
+                ..energy::Config::default()
+            }),
+            horizon: Some(100.0),
+        };
+        let model = super::write(graph, &platform, &communication, &config).unwrap();
+        assert!(model.contains(" cost: 2 x_0_0_0 + 3 x_1_0_0 + 3 x_1_1_0 + 4 x_1_1_1"));
+        assert!(model.contains(" + 0.25 z_0\n"));
+        assert!(model.contains(" - 100 o_1_2"));
+    }
+
+    #[test]
+    fn read() {
+        let content = ::parse(INPUT).unwrap();
+        let platform = Platform::new(&content, "CORE");
+        let communication = Communication::new(0.5);
+        let graph = &content.graphs[0];
+
+        let solution = "# Objective value = 4
+x_0_0_0 1
+x_1_0_0 0
+x_1_1_0 1
+x_2_0_0 1
+s_0 0
+s_1 4
+s_2 2
+f_2 5
+";
+        let schedule = super::read(solution, graph, &platform).unwrap();
+        assert_eq!(schedule.entries.iter().map(|entry| (entry.element, entry.start, entry.finish))
+                                          .collect::<Vec<_>>(),
+                   vec![(0, 0.0, 2.0), (1, 4.0, 5.0), (0, 2.0, 5.0)]);
+        assert!(schedule::check(&schedule, graph, &platform, &communication,
+                                &HashMap::new()).is_empty());
+
+        let solution = "      0 x_0_0_0                  1                       0
+      3 x_1_1_1                  1                       2
+      4 x_2_0_0                  1                       3
+      8 s_1                      4                       0
+";
+        let schedule = super::read(solution, graph, &platform).unwrap();
+        assert_eq!(schedule.entries[1].version, 1);
+        assert_eq!(schedule.entries[2].start, 0.0);
+
+        let solution = r#"<CPLEXSolution version="1.2">
+ <variables>
+  <variable name="x_0_0_0" index="0" value="1"/>
+  <variable name="x_1_0_0" index="1" value="1"/>
+  <variable name="x_2_1_0" index="4" value="1"/>
+  <variable name="s_1" index="7" value="2"/>
+ </variables>
+</CPLEXSolution>
+"#;
+        let schedule = super::read(solution, graph, &platform).unwrap();
+        assert_eq!(schedule.entries[2].element, 1);
+        assert_eq!(schedule.entries[1].finish, 5.0);
+
+        let error = super::read("x_0_0_0 1\nx_1_0_0 one\n", graph, &platform).unwrap_err();
+        assert_eq!(error.line, 2);
+        let error = super::read("x_0_0_0 1\nx_1_0_0 1\n", graph, &platform).unwrap_err();
+        assert_eq!(error.message, "the task 2 is not assigned");
+        assert_eq!(error.line, 0);
+        let error = super::read("s_0 0\nx_0_1_0 1\ns_1 2\n", graph, &platform).unwrap_err();
+        assert!(error.message.starts_with("the task 0 cannot be executed"));
+        assert_eq!(error.line, 2);
+    }
+}
@@ -0,0 +1,564 @@
+//! Optimization of mappings using metaheuristics.
+//!
+//! A solution is a mapping of the tasks of a graph onto processing elements,
+//! which is turned into a schedule by the list scheduler. The priorities of
+//! the tasks are kept fixed during the search and are taken from the initial
+//! solution produced by HEFT.
+
+use std::collections::HashSet;
+
+use Graph;
+use communication::Communication;
+use energy;
+use mapping::Mapping;
+use platform::Platform;
+use random::Generator;
+use schedule::{self, Schedule};
+
+/// An optimization problem.
+pub struct Problem<'a> {
+    /// The task graph.
+    pub graph: &'a Graph,
+    /// The processing elements.
+    pub platform: &'a Platform<'a>,
+    /// The communication model.
+    pub communication: &'a Communication,
+    choices: Vec<Vec<(usize, Vec<usize>)>>,
+}
+
+/// An objective to minimize.
+pub trait Objective {
+    /// Evaluate a solution.
+    fn evaluate(&self, problem: &Problem, mapping: &Mapping, schedule: &Schedule) -> f64;
+}
+
+/// The makespan of a schedule.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Makespan;
+
+/// The energy consumed by a schedule over a hyperperiod.
+#[derive(Clone, Debug)]
+pub struct Energy {
+    /// The hyperperiod.
+    pub hyperperiod: f64,
+    /// The configuration of energy estimation.
+    pub config: energy::Config,
+}
+
+/// The number of missed hard deadlines.
+///
+/// In order to guide the search, the total tardiness `t` is added to the
+/// number of misses as `t / (1 + t)`, which is always less than one.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Misses;
+
+/// The total price of the processing elements that have tasks mapped onto them.
+#[derive(Clone, Debug)]
+pub struct Price {
+    /// The name of the attribute with the price.
+    pub attribute: String,
+}
+
+/// A modification of a mapping.
+///
+/// Tasks are referred to by their positions in the graph.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Move {
+    /// Move a task to another processing element using its fastest version
+    /// there.
+    Reassign { task: usize, element: usize },
+    /// Exchange the processing elements of two tasks using their fastest
+    /// versions there.
+    Swap { first: usize, second: usize },
+    /// Change the version of a task on its processing element.
+    Version { task: usize, version: usize },
+}
+
+/// A configuration of simulated annealing.
+#[derive(Clone, Debug)]
+pub struct Annealing {
+    /// The seed of the random number generator.
+    pub seed: u64,
+    /// The number of iterations.
+    pub iterations: usize,
+    /// The initial temperature relative to the cost of the initial solution.
+    pub temperature: f64,
+    /// The factor the temperature is multiplied by after each iteration.
+    pub cooling: f64,
+}
+
+/// A configuration of tabu search.
+#[derive(Clone, Debug)]
+pub struct Tabu {
+    /// The seed of the random number generator.
+    pub seed: u64,
+    /// The number of iterations.
+    pub iterations: usize,
+    /// The number of random moves considered at each iteration.
+    pub neighbors: usize,
+    /// The number of iterations a moved task stays tabu.
+    pub tenure: usize,
+}
+
+/// The outcome of an optimization.
+#[derive(Clone, Debug)]
+pub struct Solution {
+    /// The best mapping.
+    pub mapping: Mapping,
+    /// The schedule of the best mapping.
+    pub schedule: Schedule,
+    /// The cost of the best mapping.
+    pub cost: f64,
+    /// The progress of the search.
+    pub log: Vec<Step>,
+}
+
+/// An iteration of an optimization.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Step {
+    /// The cost of the current solution.
+    pub current: f64,
+    /// The cost of the best solution found so far.
+    pub best: f64,
+}
+
+impl<'a> Problem<'a> {
+    /// Create a problem.
+    pub fn new(graph: &'a Graph, platform: &'a Platform<'a>, communication: &'a Communication)
+               -> Problem<'a> {
+
+        let choices = graph.tasks.iter().map(|task| {
+            platform.elements.iter().enumerate().filter_map(|(j, element)| {
+                let versions = element.versions(task.kind).into_iter().filter(|&version| {
+                    element.execution_time(task.kind, version).is_some()
+                }).collect::<Vec<_>>();
+                if versions.is_empty() { None } else { Some((j, versions)) }
+            }).collect()
+        }).collect();
+
+        Problem {
+            graph: graph,
+            platform: platform,
+            communication: communication,
+            choices: choices,
+        }
+    }
+
+    /// Return the mapping of the schedule produced by HEFT.
+    ///
+    /// The assignments follow the order of the tasks in the graph.
+    pub fn initial(&self) -> Option<Mapping> {
+        schedule::heft(self.graph, self.platform, self.communication).map(|schedule| {
+            Mapping::from(&schedule)
+        })
+    }
+
+    /// Return the processing elements that can execute a task together with
+    /// the versions available on each of them.
+    #[inline]
+    pub fn choices(&self, task: usize) -> &[(usize, Vec<usize>)] {
+        &self.choices[task]
+    }
+
+    /// Schedule a mapping.
+    #[inline]
+    pub fn schedule(&self, mapping: &Mapping) -> Option<Schedule> {
+        schedule::list(self.graph, self.platform, self.communication, mapping)
+    }
+
+    /// Apply a move to a mapping.
+    ///
+    /// The function returns `None` if the move is not applicable.
+    pub fn apply(&self, mapping: &Mapping, step: Move) -> Option<Mapping> {
+        let mut mapping = mapping.clone();
+        match step {
+            Move::Reassign { task, element } => {
+                let version = match self.fastest(task, element) {
+                    Some(version) => version,
+                    _ => return None,
+                };
+                let ref mut assignment = mapping.assignments[task];
+                assignment.element = element;
+                assignment.version = version;
+            },
+            Move::Swap { first, second } => {
+                let (one, other) = (mapping.assignments[first].element,
+                                    mapping.assignments[second].element);
+                match (self.fastest(first, other), self.fastest(second, one)) {
+                    (Some(version), Some(another)) => {
+                        mapping.assignments[first].element = other;
+                        mapping.assignments[first].version = version;
+                        mapping.assignments[second].element = one;
+                        mapping.assignments[second].version = another;
+                    },
+                    _ => return None,
+                }
+            },
+            Move::Version { task, version } => {
+                let element = mapping.assignments[task].element;
+                match self.versions(task, element) {
+                    Some(versions) if versions.contains(&version) => {},
+                    _ => return None,
+                }
+                mapping.assignments[task].version = version;
+            },
+        }
+        Some(mapping)
+    }
+
+    /// Choose a random applicable move that changes a mapping.
+    fn neighbor(&self, mapping: &Mapping, generator: &mut Generator) -> Option<Move> {
+        let count = mapping.assignments.len();
+        if count == 0 {
+            return None;
+        }
+        for _ in 0..(16 * count) {
+            let task = generator.below(count);
+            let ref assignment = mapping.assignments[task];
+            let step = match generator.below(3) {
+                0 => {
+                    let ref choices = self.choices[task];
+                    let element = choices[generator.below(choices.len())].0;
+                    if element == assignment.element {
+                        continue;
+                    }
+                    Move::Reassign { task: task, element: element }
+                },
+                1 => {
+                    let other = generator.below(count);
+                    if mapping.assignments[other].element == assignment.element {
+                        continue;
+                    }
+                    Move::Swap { first: task, second: other }
+                },
+                _ => {
+                    let versions = match self.versions(task, assignment.element) {
+                        Some(versions) if versions.len() > 1 => versions,
+                        _ => continue,
+                    };
+                    let version = versions[generator.below(versions.len())];
+                    if version == assignment.version {
+                        continue;
+                    }
+                    Move::Version { task: task, version: version }
+                },
+            };
+            if self.apply(mapping, step).is_some() {
+                return Some(step);
+            }
+        }
+        None
+    }
+
+    /// Evaluate a mapping.
+    pub fn evaluate<T: Objective + ?Sized>(&self, objective: &T, mapping: &Mapping)
+                                           -> Option<(Schedule, f64)> {
+        self.schedule(mapping).map(|schedule| {
+            let cost = objective.evaluate(self, mapping, &schedule);
+            (schedule, cost)
+        })
+    }
+
+    fn versions(&self, task: usize, element: usize) -> Option<&[usize]> {
+        self.choices[task].iter().find(|&&(other, _)| other == element)
+                                 .map(|choice| &choice.1[..])
+    }
+
+    fn fastest(&self, task: usize, element: usize) -> Option<usize> {
+        self.platform.elements.get(element).and_then(|element| {
+            element.fastest(self.graph.tasks[task].kind)
+        }).map(|(version, _)| version)
+    }
+}
+
+impl<F> Objective for F where F: Fn(&Problem, &Mapping, &Schedule) -> f64 {
+    #[inline]
+    fn evaluate(&self, problem: &Problem, mapping: &Mapping, schedule: &Schedule) -> f64 {
+        self(problem, mapping, schedule)
+    }
+}
+
+impl Objective for Makespan {
+    #[inline]
+    fn evaluate(&self, _: &Problem, _: &Mapping, schedule: &Schedule) -> f64 {
+        schedule.makespan()
+    }
+}
+
+impl Objective for Energy {
+    fn evaluate(&self, problem: &Problem, _: &Mapping, schedule: &Schedule) -> f64 {
+        match energy::estimate(schedule, problem.graph, problem.platform, problem.communication,
+                               self.hyperperiod, &self.config) {
+            Some(energy) => energy.total(),
+            _ => f64::INFINITY,
+        }
+    }
+}
+
+impl Objective for Misses {
+    fn evaluate(&self, problem: &Problem, _: &Mapping, schedule: &Schedule) -> f64 {
+        let (mut misses, mut tardiness) = (0.0, 0.0);
+        for deadline in problem.graph.deadlines.iter() {
+            if let Some(entry) = schedule.find(deadline.on) {
+                let delay = entry.finish - deadline.at as f64;
+                if delay > 0.0 {
+                    misses += 1.0;
+                    tardiness += delay;
+                }
+            }
+        }
+        misses + tardiness / (1.0 + tardiness)
+    }
+}
+
+impl Default for Price {
+    fn default() -> Price {
+        Price { attribute: String::from("price") }
+    }
+}
+
+impl Objective for Price {
+    fn evaluate(&self, problem: &Problem, mapping: &Mapping, _: &Schedule) -> f64 {
+        let used = mapping.assignments.iter().map(|assignment| assignment.element)
+                                             .collect::<HashSet<_>>();
+        used.iter().fold(0.0, |sum, &element| {
+            sum + problem.platform.elements[element].attribute(&self.attribute).unwrap_or(0.0)
+        })
+    }
+}
+
+impl Default for Annealing {
+    fn default() -> Annealing {
+        Annealing { seed: 0, iterations: 1000, temperature: 0.1, cooling: 0.995 }
+    }
+}
+
+impl Default for Tabu {
+    fn default() -> Tabu {
+        Tabu { seed: 0, iterations: 200, neighbors: 20, tenure: 7 }
+    }
+}
+
+/// Minimize an objective using simulated annealing.
+///
+/// At each iteration, a random move is applied to the current solution, and
+/// the outcome is accepted if it is better or, otherwise, with a probability
+/// that decreases with the degradation and the temperature. The function
+/// returns `None` if the problem has no initial solution.
+pub fn anneal<T: Objective + ?Sized>(problem: &Problem, objective: &T, config: &Annealing)
+                                     -> Option<Solution> {
+
+    let mut generator = Generator::new(config.seed);
+    let mapping = match problem.initial() {
+        Some(mapping) => mapping,
+        _ => return None,
+    };
+    let (schedule, cost) = match problem.evaluate(objective, &mapping) {
+        Some(outcome) => outcome,
+        _ => return None,
+    };
+
+    let scale = if cost.abs() > 0.0 && cost.is_finite() { cost.abs() } else { 1.0 };
+    let mut temperature = config.temperature;
+    let mut current = (mapping.clone(), cost);
+    let mut best = Solution { mapping: mapping, schedule: schedule, cost: cost, log: vec![] };
+
+    for _ in 0..config.iterations {
+        let candidate = problem.neighbor(&current.0, &mut generator).and_then(|step| {
+            problem.apply(&current.0, step)
+        });
+        if let Some(mapping) = candidate {
+            if let Some((schedule, cost)) = problem.evaluate(objective, &mapping) {
+                let delta = (cost - current.1) / scale;
+                if delta <= 0.0 || generator.uniform() < (-delta / temperature).exp() {
+                    if cost < best.cost {
+                        best.mapping = mapping.clone();
+                        best.schedule = schedule;
+                        best.cost = cost;
+                    }
+                    current = (mapping, cost);
+                }
+            }
+        }
+        temperature *= config.cooling;
+        best.log.push(Step { current: current.1, best: best.cost });
+    }
+
+    Some(best)
+}
+
+/// Minimize an objective using tabu search.
+///
+/// At each iteration, a number of random moves are evaluated, and the best one
+/// is taken even if it degrades the current solution, unless it touches a task
+/// that has been moved recently. A tabu move is still taken if it leads to a
+/// solution better than the best one found so far. The function returns
+/// `None` if the problem has no initial solution.
+pub fn tabu<T: Objective + ?Sized>(problem: &Problem, objective: &T, config: &Tabu)
+                                   -> Option<Solution> {
+
+    let mut generator = Generator::new(config.seed);
+    let mapping = match problem.initial() {
+        Some(mapping) => mapping,
+        _ => return None,
+    };
+    let (schedule, cost) = match problem.evaluate(objective, &mapping) {
+        Some(outcome) => outcome,
+        _ => return None,
+    };
+
+    let mut expiry = vec![0; mapping.assignments.len()];
+    let mut current = (mapping.clone(), cost);
+    let mut best = Solution { mapping: mapping, schedule: schedule, cost: cost, log: vec![] };
+
+    for iteration in 0..config.iterations {
+        let mut chosen: Option<(Move, Mapping, Schedule, f64)> = None;
+        for _ in 0..config.neighbors {
+            let step = match problem.neighbor(&current.0, &mut generator) {
+                Some(step) => step,
+                _ => break,
+            };
+            let mapping = match problem.apply(&current.0, step) {
+                Some(mapping) => mapping,
+                _ => continue,
+            };
+            let (schedule, cost) = match problem.evaluate(objective, &mapping) {
+                Some(outcome) => outcome,
+                _ => continue,
+            };
+            let tabu = touched(step).iter().any(|&task| expiry[task] > iteration);
+            if tabu && cost >= best.cost {
+                continue;
+            }
+            match chosen {
+                Some((_, _, _, other)) if other <= cost => {},
+                _ => chosen = Some((step, mapping, schedule, cost)),
+            }
+        }
+        if let Some((step, mapping, schedule, cost)) = chosen {
+            for task in touched(step) {
+                expiry[task] = iteration + 1 + config.tenure;
+            }
+            if cost < best.cost {
+                best.mapping = mapping.clone();
+                best.schedule = schedule;
+                best.cost = cost;
+            }
+            current = (mapping, cost);
+        }
+        best.log.push(Step { current: current.1, best: best.cost });
+    }
+
+    Some(best)
+}
+
+fn touched(step: Move) -> Vec<usize> {
+    match step {
+        Move::Reassign { task, .. } | Move::Version { task, .. } => vec![task],
+        Move::Swap { first, second } => vec![first, second],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use communication::Communication;
+    use platform::Platform;
+    use random::Generator;
+    use super::{Annealing, Makespan, Misses, Move, Price, Problem, Tabu};
+
+    const INPUT: &str = "@GRAPH 0 {
+    TASK t0_0 TYPE 0
+    TASK t0_1 TYPE 1
+    TASK t0_2 TYPE 1
+    TASK t0_3 TYPE 0
+    ARC a0_0 FROM t0_0 TO t0_1 TYPE 0
+    ARC a0_1 FROM t0_0 TO t0_2 TYPE 0
+    ARC a0_2 FROM t0_1 TO t0_3 TYPE 0
+    ARC a0_3 FROM t0_2 TO t0_3 TYPE 0
+    HARD_DEADLINE d0_0 ON t0_3 AT 7
+}
+@CORE 0 {
+# price
+  10
+#----
+# type version execution_time
+  0    0       1
+  1    0       4
+  1    1       3
+}
+@CORE 1 {
+# price
+  1
+#----
+# type version execution_time
+  0    0       2
+  1    0       5
+}
+";
+
+    #[test]
+    fn apply() {
+        let content = ::parse(INPUT).unwrap();
+        let platform = Platform::new(&content, "CORE");
+        let communication = Communication::new(1.0);
+        let problem = Problem::new(&content.graphs[0], &platform, &communication);
+
+        let mapping = problem.initial().unwrap();
+        assert_eq!(mapping.assignments[1].element, 0);
+        assert_eq!(mapping.assignments[1].version, 1);
+
+        let other = problem.apply(&mapping, Move::Reassign { task: 1, element: 1 }).unwrap();
+        assert_eq!((other.assignments[1].element, other.assignments[1].version), (1, 0));
+
+        let other = problem.apply(&other, Move::Swap { first: 1, second: 2 }).unwrap();
+        assert_eq!((other.assignments[1].element, other.assignments[1].version), (0, 1));
+
+        assert!(problem.apply(&mapping, Move::Version { task: 1, version: 0 }).is_some());
+        assert!(problem.apply(&mapping, Move::Version { task: 1, version: 2 }).is_none());
+        assert!(problem.apply(&mapping, Move::Reassign { task: 1, element: 2 }).is_none());
+
+        let mut generator = Generator::new(0);
+        for _ in 0..100 {
+            let step = problem.neighbor(&mapping, &mut generator).unwrap();
+            let other = problem.apply(&mapping, step).unwrap();
+            assert!(other != mapping);
+        }
+    }
+
+    #[test]
+    fn anneal() {
+        let content = ::parse(INPUT).unwrap();
+        let platform = Platform::new(&content, "CORE");
+        let communication = Communication::new(1.0);
+        let problem = Problem::new(&content.graphs[0], &platform, &communication);
+
+        let config = Annealing { seed: 42, iterations: 200, ..Annealing::default() };
+        let solution = super::anneal(&problem, &Price::default(), &config).unwrap();
+        assert_eq!(solution.cost, 1.0);
+        assert_eq!(solution.log.len(), 200);
+        assert!(solution.log.windows(2).all(|steps| steps[1].best <= steps[0].best));
+        assert!(solution.mapping.assignments.iter().all(|assignment| assignment.element == 1));
+        assert_eq!(solution.schedule.makespan(), 14.0);
+
+        let other = super::anneal(&problem, &Price::default(), &config).unwrap();
+        assert_eq!(solution.log, other.log);
+    }
+
+    #[test]
+    fn tabu() {
+        let content = ::parse(INPUT).unwrap();
+        let platform = Platform::new(&content, "CORE");
+        let communication = Communication::new(1.0);
+        let problem = Problem::new(&content.graphs[0], &platform, &communication);
+
+        let initial = problem.evaluate(&Makespan, &problem.initial().unwrap()).unwrap().1;
+        let config = Tabu { seed: 7, iterations: 50, ..Tabu::default() };
+        let solution = super::tabu(&problem, &Makespan, &config).unwrap();
+        assert!(solution.cost <= initial);
+        assert_eq!(solution.cost, 8.0);
+        assert_eq!(solution.log.len(), 50);
+
+        let solution = super::tabu(&problem, &Misses, &config).unwrap();
+        assert_eq!(solution.cost, 1.5);
+    }
+}
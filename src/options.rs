@@ -0,0 +1,474 @@
+//! Options of the `tgff` command-line utility.
+//!
+//! An option file, which has the `.tgffopt` extension, is a sequence of
+//! statements, one per line. A statement is either a parameter, which is a
+//! name followed by arguments, or a command, which is a bare name that makes
+//! the utility write something using the parameters given so far. A line
+//! ending with a backslash continues on the next one, and everything after
+//! `#` is a comment.
+
+use std::fmt;
+use std::str::FromStr;
+
+use {Error, Result};
+use generator;
+
+pub use generator::Attribute;
+
+/// The content of an option file.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Options {
+    /// The statements in the order of appearance.
+    pub statements: Vec<Statement>,
+}
+
+/// A statement of an option file.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Statement {
+    /// A parameter.
+    Parameter(Parameter),
+    /// A command.
+    Command(Command),
+}
+
+/// A parameter of an option file.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Parameter {
+    /// The seed of the random number generator (`seed`).
+    Seed(u64),
+    /// The name of task graphs (`tg_label`).
+    GraphLabel(String),
+    /// The number of task graphs (`tg_cnt`).
+    GraphCount(usize),
+    /// The average number of tasks per graph and its deviation (`task_cnt`).
+    TaskCount(f64, f64),
+    /// The number of task types (`task_type_cnt`).
+    TaskTypeCount(usize),
+    /// The number of arc types (`trans_type_cnt`).
+    ArcTypeCount(usize),
+    /// The average time per task including communication
+    /// (`task_trans_time`).
+    TaskTransferTime(f64),
+    /// The relative deviation of deadlines (`deadline_jitter`).
+    DeadlineJitter(f64),
+    /// The multipliers of periods (`period_mul`).
+    PeriodMultipliers(Vec<f64>),
+    /// The maximal numbers of incoming and outgoing arcs of a task
+    /// (`task_degree`).
+    TaskDegree(usize, usize),
+    /// The uniqueness of task types (`task_unique`).
+    TaskUnique(bool),
+    /// The name of data tables (`table_label`).
+    TableLabel(String),
+    /// The number of data tables (`table_cnt`).
+    TableCount(usize),
+    /// The attributes of data tables (`table_attrib`).
+    TableAttributes(Vec<Attribute>),
+    /// The attributes of task types stored in data tables (`type_attrib`).
+    TypeAttributes(Vec<Attribute>),
+    /// Any other parameter.
+    Other(String, Vec<String>),
+}
+
+/// A command of an option file.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Command {
+    /// Write task graphs and data tables (`tg_write`).
+    GraphWrite,
+    /// Write data tables of processing elements (`pe_write`).
+    TableWrite,
+    /// Write data tables of communication (`trans_write`).
+    TransferWrite,
+    /// Write miscellaneous data tables (`misc_write`).
+    MiscellaneousWrite,
+    /// Write task graphs in the Encapsulated PostScript format (`eps_write`).
+    EpsWrite,
+    /// Write task graphs in the VCG format (`vcg_write`).
+    VcgWrite,
+}
+
+/// Parse a string containing an option file.
+pub fn parse(input: &str) -> Result<Options> {
+    let mut statements = vec![];
+
+    let mut buffer = String::new();
+    let mut start = 0;
+    for (i, line) in input.lines().enumerate() {
+        let line = match line.find('#') {
+            Some(end) => &line[..end],
+            _ => line,
+        };
+        if buffer.is_empty() {
+            start = i + 1;
+        }
+        let line = line.trim();
+        if let Some(line) = line.strip_suffix('\\') {
+            buffer.push_str(line);
+            buffer.push(' ');
+            continue;
+        }
+        buffer.push_str(line);
+        if !buffer.trim().is_empty() {
+            statements.push(statement(&buffer, start)?);
+        }
+        buffer.clear();
+    }
+    if !buffer.trim().is_empty() {
+        statements.push(statement(&buffer, start)?);
+    }
+
+    Ok(Options { statements: statements })
+}
+
+impl Options {
+    /// Return the value of a parameter.
+    ///
+    /// If a parameter is given several times, the last value is taken.
+    pub fn find<F, T>(&self, select: F) -> Option<T> where F: Fn(&Parameter) -> Option<T> {
+        self.statements.iter().rev().filter_map(|statement| match *statement {
+            Statement::Parameter(ref parameter) => select(parameter),
+            _ => None,
+        }).next()
+    }
+
+    /// Return a configuration of graph generation.
+    ///
+    /// Parameters that are not given keep the default values of the
+    /// configuration.
+    pub fn graphs(&self) -> generator::Config {
+        let mut config = generator::Config::default();
+        for statement in self.statements.iter() {
+            let parameter = match *statement {
+                Statement::Parameter(ref parameter) => parameter,
+                _ => continue,
+            };
+            match *parameter {
+                Parameter::Seed(seed) => config.seed = seed,
+                Parameter::GraphLabel(ref label) => config.label = label.clone(),
+                Parameter::GraphCount(count) => config.graphs = count,
+                Parameter::TaskCount(average, deviation) => config.tasks = (average, deviation),
+                Parameter::TaskTypeCount(count) => config.task_types = count,
+                Parameter::ArcTypeCount(count) => config.arc_types = count,
+                Parameter::TaskTransferTime(time) => config.transfer_time = time,
+                Parameter::DeadlineJitter(jitter) => config.deadline_jitter = jitter,
+                Parameter::PeriodMultipliers(ref multipliers) => {
+                    config.period_multipliers = multipliers.clone();
+                },
+                Parameter::TaskDegree(fan_in, fan_out) => config.degree = (fan_in, fan_out),
+                _ => {},
+            }
+        }
+        config
+    }
+
+    /// Return a configuration of table generation.
+    ///
+    /// Parameters that are not given keep the default values of the
+    /// configuration. An attribute called `price` given via `table_attrib`
+    /// replaces the default price.
+    pub fn tables(&self) -> generator::Tables {
+        let mut config = generator::Tables::default();
+        for statement in self.statements.iter() {
+            let parameter = match *statement {
+                Statement::Parameter(ref parameter) => parameter,
+                _ => continue,
+            };
+            match *parameter {
+                Parameter::Seed(seed) => config.seed = seed,
+                Parameter::TableLabel(ref label) => config.label = label.clone(),
+                Parameter::TableCount(count) => config.count = count,
+                Parameter::TaskTypeCount(count) => config.task_types = count,
+                Parameter::TableAttributes(ref attributes) => {
+                    config.attributes.clear();
+                    for attribute in attributes.iter() {
+                        if attribute.name == "price" {
+                            config.price = attribute.clone();
+                        } else {
+                            config.attributes.push(attribute.clone());
+                        }
+                    }
+                },
+                Parameter::TypeAttributes(ref attributes) => config.columns = attributes.clone(),
+                _ => {},
+            }
+        }
+        config
+    }
+}
+
+impl fmt::Display for Options {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        for statement in self.statements.iter() {
+            writeln!(formatter, "{}", statement)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for Statement {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Statement::Parameter(ref parameter) => parameter.fmt(formatter),
+            Statement::Command(ref command) => command.fmt(formatter),
+        }
+    }
+}
+
+impl fmt::Display for Parameter {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Parameter::Seed(seed) => write!(formatter, "seed {}", seed),
+            Parameter::GraphLabel(ref label) => write!(formatter, "tg_label {}", label),
+            Parameter::GraphCount(count) => write!(formatter, "tg_cnt {}", count),
+            Parameter::TaskCount(average, deviation) => {
+                write!(formatter, "task_cnt {} {}", average, deviation)
+            },
+            Parameter::TaskTypeCount(count) => write!(formatter, "task_type_cnt {}", count),
+            Parameter::ArcTypeCount(count) => write!(formatter, "trans_type_cnt {}", count),
+            Parameter::TaskTransferTime(time) => write!(formatter, "task_trans_time {}", time),
+            Parameter::DeadlineJitter(jitter) => write!(formatter, "deadline_jitter {}", jitter),
+            Parameter::PeriodMultipliers(ref multipliers) => {
+                write!(formatter, "period_mul")?;
+                for multiplier in multipliers.iter() {
+                    write!(formatter, " {}", multiplier)?;
+                }
+                Ok(())
+            },
+            Parameter::TaskDegree(fan_in, fan_out) => {
+                write!(formatter, "task_degree {} {}", fan_in, fan_out)
+            },
+            Parameter::TaskUnique(unique) => write!(formatter, "task_unique {}", unique),
+            Parameter::TableLabel(ref label) => write!(formatter, "table_label {}", label),
+            Parameter::TableCount(count) => write!(formatter, "table_cnt {}", count),
+            Parameter::TableAttributes(ref attributes) => {
+                write_attributes(formatter, "table_attrib", attributes)
+            },
+            Parameter::TypeAttributes(ref attributes) => {
+                write_attributes(formatter, "type_attrib", attributes)
+            },
+            Parameter::Other(ref name, ref arguments) => {
+                write!(formatter, "{}", name)?;
+                for argument in arguments.iter() {
+                    write!(formatter, " {}", argument)?;
+                }
+                Ok(())
+            },
+        }
+    }
+}
+
+impl fmt::Display for Command {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str(match *self {
+            Command::GraphWrite => "tg_write",
+            Command::TableWrite => "pe_write",
+            Command::TransferWrite => "trans_write",
+            Command::MiscellaneousWrite => "misc_write",
+            Command::EpsWrite => "eps_write",
+            Command::VcgWrite => "vcg_write",
+        })
+    }
+}
+
+impl fmt::Display for Attribute {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(formatter, "{} {} {} {} {}", self.name, self.average, self.multiplier,
+               self.jitter, self.round)
+    }
+}
+
+fn statement(text: &str, line: usize) -> Result<Statement> {
+    let mut tokens = text.split_whitespace();
+    let name = tokens.next().unwrap();
+    let arguments = tokens.collect::<Vec<_>>();
+
+    let command = match name {
+        "tg_write" => Some(Command::GraphWrite),
+        "pe_write" => Some(Command::TableWrite),
+        "trans_write" => Some(Command::TransferWrite),
+        "misc_write" => Some(Command::MiscellaneousWrite),
+        "eps_write" => Some(Command::EpsWrite),
+        "vcg_write" => Some(Command::VcgWrite),
+        _ => None,
+    };
+    if let Some(command) = command {
+        if !arguments.is_empty() {
+            raise!(line, "the command `{}` does not take arguments", name);
+        }
+        return Ok(Statement::Command(command));
+    }
+
+    macro_rules! get(
+        ($count:expr) => ({
+            if arguments.len() != $count {
+                raise!(line, "expected {} argument(s) of `{}`", $count, name);
+            }
+            values(&arguments, name, line)?
+        });
+    );
+
+    let parameter = match name {
+        "seed" => Parameter::Seed(get!(1).remove(0)),
+        "tg_label" => Parameter::GraphLabel(get!(1).remove(0)),
+        "tg_cnt" => Parameter::GraphCount(get!(1).remove(0)),
+        "task_cnt" => {
+            let values = get!(2);
+            Parameter::TaskCount(values[0], values[1])
+        },
+        "task_type_cnt" => Parameter::TaskTypeCount(get!(1).remove(0)),
+        "trans_type_cnt" => Parameter::ArcTypeCount(get!(1).remove(0)),
+        "task_trans_time" => Parameter::TaskTransferTime(get!(1).remove(0)),
+        "deadline_jitter" => Parameter::DeadlineJitter(get!(1).remove(0)),
+        "period_mul" => {
+            if arguments.is_empty() {
+                raise!(line, "expected arguments of `{}`", name);
+            }
+            Parameter::PeriodMultipliers(values(&arguments, name, line)?)
+        },
+        "task_degree" => {
+            let values = get!(2);
+            Parameter::TaskDegree(values[0], values[1])
+        },
+        "task_unique" => Parameter::TaskUnique(get!(1).remove(0)),
+        "table_label" => Parameter::TableLabel(get!(1).remove(0)),
+        "table_cnt" => Parameter::TableCount(get!(1).remove(0)),
+        "table_attrib" => Parameter::TableAttributes(attributes(&arguments, name, line)?),
+        "type_attrib" => Parameter::TypeAttributes(attributes(&arguments, name, line)?),
+        _ => Parameter::Other(name.to_string(),
+                              arguments.iter().map(|argument| argument.to_string()).collect()),
+    };
+
+    Ok(Statement::Parameter(parameter))
+}
+
+fn values<T: FromStr>(arguments: &[&str], name: &str, line: usize) -> Result<Vec<T>> {
+    let mut values = Vec::with_capacity(arguments.len());
+    for argument in arguments.iter() {
+        match argument.parse() {
+            Ok(value) => values.push(value),
+            _ => raise!(line, "failed to parse the argument `{}` of `{}`", argument, name),
+        }
+    }
+    Ok(values)
+}
+
+fn attributes(arguments: &[&str], name: &str, line: usize) -> Result<Vec<Attribute>> {
+    let text = arguments.join(" ");
+    let mut attributes = vec![];
+    if arguments.is_empty() {
+        return Ok(attributes);
+    }
+    for specification in text.split(',') {
+        let tokens = specification.split_whitespace().collect::<Vec<_>>();
+        if tokens.len() < 3 || tokens.len() > 5 {
+            raise!(line, "expected a name and two to four values in `{}`", name);
+        }
+        let values = values::<f64>(&tokens[1..], name, line)?;
+        attributes.push(Attribute {
+            name: tokens[0].to_string(),
+            average: values[0],
+            multiplier: values[1],
+            jitter: values.get(2).cloned().unwrap_or(0.0),
+            round: values.get(3).cloned().unwrap_or(0.0),
+        });
+    }
+    Ok(attributes)
+}
+
+fn write_attributes(formatter: &mut fmt::Formatter, name: &str, attributes: &[Attribute])
+                    -> fmt::Result {
+
+    write!(formatter, "{}", name)?;
+    for (i, attribute) in attributes.iter().enumerate() {
+        let separator = if i + 1 < attributes.len() { "," } else { "" };
+        write!(formatter, " \\\n  {}{}", attribute, separator)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Attribute, Command, Parameter, Statement};
+
+    const INPUT: &str = "seed 4
+
+# Graph label
+tg_label GRAPH
+task_cnt 40 0.001 # per graph
+period_mul 1 0.5 2
+task_degree 3 4
+task_unique false
+tg_write
+type_attrib \\
+  dynamic_power 10 9 0 0.01, \\
+  execution_time 0.020 0.010
+prob_multi_start_nodes 0.5
+";
+
+    #[test]
+    fn parse() {
+        let options = super::parse(INPUT).unwrap();
+        assert_eq!(options.statements, vec![
+            Statement::Parameter(Parameter::Seed(4)),
+            Statement::Parameter(Parameter::GraphLabel(String::from("GRAPH"))),
+            Statement::Parameter(Parameter::TaskCount(40.0, 0.001)),
+            Statement::Parameter(Parameter::PeriodMultipliers(vec![1.0, 0.5, 2.0])),
+            Statement::Parameter(Parameter::TaskDegree(3, 4)),
+            Statement::Parameter(Parameter::TaskUnique(false)),
+            Statement::Command(Command::GraphWrite),
+            Statement::Parameter(Parameter::TypeAttributes(vec![
+                Attribute {
+                    name: String::from("dynamic_power"),
+                    average: 10.0,
+                    multiplier: 9.0,
+                    jitter: 0.0,
+                    round: 0.01,
+                },
+                Attribute {
+                    name: String::from("execution_time"),
+                    average: 0.02,
+                    multiplier: 0.01,
+                    jitter: 0.0,
+                    round: 0.0,
+                },
+            ])),
+            Statement::Parameter(Parameter::Other(String::from("prob_multi_start_nodes"),
+                                                  vec![String::from("0.5")])),
+        ]);
+
+        let config = options.graphs();
+        assert_eq!(config.seed, 4);
+        assert_eq!(config.degree, (3, 4));
+        assert_eq!(options.find(|parameter| match *parameter {
+            Parameter::TaskUnique(unique) => Some(unique),
+            _ => None,
+        }), Some(false));
+    }
+
+    #[test]
+    fn parse_failure() {
+        let error = super::parse("seed 4\n\ntg_cnt two\n").unwrap_err();
+        assert_eq!(error.line, 3);
+        let error = super::parse("task_degree \\\n 3\ntg_write").unwrap_err();
+        assert_eq!(error.line, 1);
+        let error = super::parse("tg_write now\n").unwrap_err();
+        assert_eq!(error.message, "the command `tg_write` does not take arguments");
+        let error = super::parse("type_attrib price 1, time\n").unwrap_err();
+        assert_eq!(error.line, 1);
+    }
+
+    #[test]
+    fn write() {
+        let options = super::parse(INPUT).unwrap();
+        let output = options.to_string();
+        assert!(output.contains("type_attrib \\\n  dynamic_power 10 9 0 0.01, \\\n  \
+                                 execution_time 0.02 0.01 0 0\n"));
+        assert_eq!(super::parse(&output).unwrap(), options);
+
+        let mut options = options;
+        options.statements.push(Statement::Parameter(Parameter::TableAttributes(vec![])));
+        let output = options.to_string();
+        assert!(output.ends_with("table_attrib\n"));
+        assert_eq!(super::parse(&output).unwrap(), options);
+    }
+}
@@ -0,0 +1,381 @@
+//! Multi-objective exploration of mappings.
+//!
+//! The exploration is performed by the nondominated sorting genetic algorithm
+//! (NSGA-II) using the chromosomes and operators of the `genetic` module. All
+//! objectives are minimized.
+
+use std::cmp::Ordering;
+
+use genetic::{self, Chromosome, Crossover};
+use mapping::Mapping;
+use optimization::{Objective, Problem};
+use random::Generator;
+use schedule::Schedule;
+
+/// A configuration of multi-objective exploration.
+#[derive(Clone, Debug)]
+pub struct Config {
+    /// The seed of the random number generator.
+    pub seed: u64,
+    /// The number of chromosomes in the population.
+    pub population: usize,
+    /// The number of generations.
+    pub generations: usize,
+    /// The crossover operator.
+    pub crossover: Crossover,
+    /// The probability of crossover of two parents.
+    pub crossover_rate: f64,
+    /// The probability of mutation of each gene.
+    pub mutation_rate: f64,
+    /// The number of threads used for evaluation.
+    pub threads: usize,
+}
+
+/// A point of a Pareto front.
+#[derive(Clone, Debug)]
+pub struct Point {
+    /// The mapping.
+    pub mapping: Mapping,
+    /// The schedule of the mapping.
+    pub schedule: Schedule,
+    /// The values of the objectives.
+    pub objectives: Vec<f64>,
+}
+
+impl Default for Config {
+    fn default() -> Config {
+        Config {
+            seed: 0,
+            population: 50,
+            generations: 100,
+            crossover: Crossover::TwoPoint,
+            crossover_rate: 0.9,
+            mutation_rate: 0.02,
+            threads: 1,
+        }
+    }
+}
+
+/// Explore the trade-offs between several objectives.
+///
+/// The function returns the nondominated points of the final population with
+/// duplicate objective vectors removed. It returns `None` if some task cannot
+/// be executed by any processing element, if the population is empty, or if
+/// there are no objectives.
+pub fn explore(problem: &Problem, objectives: &[&(dyn Objective + Sync)], config: &Config)
+               -> Option<Vec<Point>> {
+
+    let count = problem.graph.tasks.len();
+    if config.population == 0 || objectives.is_empty() ||
+       (0..count).any(|i| problem.choices(i).is_empty()) {
+        return None;
+    }
+
+    let mut generator = Generator::new(config.seed);
+    let mut population = Vec::with_capacity(config.population);
+    if let Some(mapping) = problem.initial() {
+        population.push(Chromosome::encode(&mapping));
+    }
+    while population.len() < config.population {
+        let mut order = (0..count).collect::<Vec<_>>();
+        generator.shuffle(&mut order);
+        population.push(Chromosome {
+            genes: (0..count).map(|i| genetic::random(problem, i, &mut generator)).collect(),
+            order: order,
+        });
+    }
+    let mut outcomes = evaluate(problem, objectives, &population, config.threads);
+
+    for _ in 0..config.generations {
+        let (ranks, distances) = rank(&outcomes);
+        let better = |i: usize, j: usize| {
+            ranks[i] < ranks[j] || ranks[i] == ranks[j] && distances[i] > distances[j]
+        };
+
+        let mut offspring = Vec::with_capacity(config.population);
+        while offspring.len() < config.population {
+            let mut parents = [0; 2];
+            for parent in parents.iter_mut() {
+                let (i, j) = (generator.below(population.len()), generator.below(population.len()));
+                *parent = if better(j, i) { j } else { i };
+            }
+            let (one, other) = (&population[parents[0]], &population[parents[1]]);
+            let (mut one, mut other) = if generator.uniform() < config.crossover_rate {
+                genetic::cross(one, other, config.crossover, &mut generator)
+            } else {
+                (one.clone(), other.clone())
+            };
+            genetic::mutate(problem, &mut one, config.mutation_rate, &mut generator);
+            genetic::mutate(problem, &mut other, config.mutation_rate, &mut generator);
+            offspring.push(one);
+            if offspring.len() < config.population {
+                offspring.push(other);
+            }
+        }
+        let mut fresh = evaluate(problem, objectives, &offspring, config.threads);
+
+        population.append(&mut offspring);
+        outcomes.append(&mut fresh);
+
+        let values = outcomes.iter().map(|outcome| outcome.2.clone()).collect::<Vec<_>>();
+        let mut survivors = vec![];
+        for front in sort(&values) {
+            if survivors.len() + front.len() <= config.population {
+                survivors.extend(front);
+                continue;
+            }
+            let distances = crowd(&values, &front);
+            let mut order = (0..front.len()).collect::<Vec<_>>();
+            order.sort_by(|&i, &j| {
+                distances[j].partial_cmp(&distances[i]).unwrap_or(Ordering::Equal).then(i.cmp(&j))
+            });
+            let rest = config.population - survivors.len();
+            survivors.extend(order.into_iter().take(rest).map(|k| front[k]));
+            break;
+        }
+        survivors.sort();
+
+        let mut chosen = vec![false; population.len()];
+        for &i in survivors.iter() {
+            chosen[i] = true;
+        }
+        let mut k = 0;
+        population.retain(|_| { k += 1; chosen[k - 1] });
+        let mut k = 0;
+        outcomes.retain(|_| { k += 1; chosen[k - 1] });
+    }
+
+    let values = outcomes.iter().map(|outcome| outcome.2.clone()).collect::<Vec<_>>();
+    let mut points: Vec<Point> = vec![];
+    for i in sort(&values).into_iter().next().unwrap_or_default() {
+        let (ref mapping, ref schedule, ref objectives) = outcomes[i];
+        let schedule = match *schedule {
+            Some(ref schedule) => schedule.clone(),
+            _ => continue,
+        };
+        if points.iter().any(|point| point.objectives == *objectives) {
+            continue;
+        }
+        points.push(Point {
+            mapping: mapping.clone(),
+            schedule: schedule,
+            objectives: objectives.clone(),
+        });
+    }
+    points.sort_by(|one, other| compare(&one.objectives, &other.objectives));
+
+    Some(points)
+}
+
+/// Check if a vector of objectives dominates another one.
+pub fn dominates(one: &[f64], other: &[f64]) -> bool {
+    let mut strict = false;
+    for (&a, &b) in one.iter().zip(other.iter()) {
+        if a > b {
+            return false;
+        }
+        if a < b {
+            strict = true;
+        }
+    }
+    strict
+}
+
+/// Sort vectors of objectives into nondominated fronts.
+///
+/// The first front contains the indices of the nondominated vectors, the
+/// second one those dominated only by the vectors of the first front, and so
+/// on.
+pub fn sort(values: &[Vec<f64>]) -> Vec<Vec<usize>> {
+    let count = values.len();
+    let mut dominated = vec![vec![]; count];
+    let mut counts = vec![0; count];
+    for i in 0..count {
+        for j in 0..count {
+            if dominates(&values[i], &values[j]) {
+                dominated[i].push(j);
+            } else if dominates(&values[j], &values[i]) {
+                counts[i] += 1;
+            }
+        }
+    }
+
+    let mut fronts = vec![];
+    let mut front = (0..count).filter(|&i| counts[i] == 0).collect::<Vec<_>>();
+    while !front.is_empty() {
+        let mut next = vec![];
+        for &i in front.iter() {
+            for &j in dominated[i].iter() {
+                counts[j] -= 1;
+                if counts[j] == 0 {
+                    next.push(j);
+                }
+            }
+        }
+        next.sort();
+        fronts.push(front);
+        front = next;
+    }
+    fronts
+}
+
+/// Compute the hypervolume dominated by a set of vectors of objectives and
+/// bounded by a reference point.
+///
+/// Vectors that do not dominate the reference point strictly in every
+/// objective do not contribute to the hypervolume.
+pub fn hypervolume(values: &[Vec<f64>], reference: &[f64]) -> f64 {
+    let points = values.iter().filter(|value| {
+        value.len() == reference.len() && value.iter().zip(reference).all(|(&a, &b)| a < b)
+    }).map(|value| &value[..]).collect::<Vec<_>>();
+    slice(points, reference)
+}
+
+fn slice(mut points: Vec<&[f64]>, reference: &[f64]) -> f64 {
+    if points.is_empty() || reference.is_empty() {
+        return 0.0;
+    }
+    if reference.len() == 1 {
+        return points.iter().fold(0.0, |volume: f64, point| volume.max(reference[0] - point[0]));
+    }
+    points.sort_by(|one, other| one[0].partial_cmp(&other[0]).unwrap_or(Ordering::Equal));
+    let mut volume = 0.0;
+    for i in 0..points.len() {
+        let next = if i + 1 < points.len() { points[i + 1][0] } else { reference[0] };
+        let width = next - points[i][0];
+        if width > 0.0 {
+            let rest = points[..(i + 1)].iter().map(|point| &point[1..]).collect();
+            volume += width * slice(rest, &reference[1..]);
+        }
+    }
+    volume
+}
+
+type Outcome = (Mapping, Option<Schedule>, Vec<f64>);
+
+fn evaluate(problem: &Problem, objectives: &[&(dyn Objective + Sync)],
+            population: &[Chromosome], threads: usize) -> Vec<Outcome> {
+
+    genetic::parallel(population, threads, |chromosome| {
+        let mapping = chromosome.decode(problem);
+        match problem.schedule(&mapping) {
+            Some(schedule) => {
+                let values = objectives.iter().map(|objective| {
+                    objective.evaluate(problem, &mapping, &schedule)
+                }).collect();
+                (mapping, Some(schedule), values)
+            },
+            _ => (mapping, None, vec![f64::INFINITY; objectives.len()]),
+        }
+    })
+}
+
+fn rank(outcomes: &[Outcome]) -> (Vec<usize>, Vec<f64>) {
+    let values = outcomes.iter().map(|outcome| outcome.2.clone()).collect::<Vec<_>>();
+    let mut ranks = vec![0; values.len()];
+    let mut distances = vec![0.0; values.len()];
+    for (r, front) in sort(&values).into_iter().enumerate() {
+        for (k, distance) in crowd(&values, &front).into_iter().enumerate() {
+            ranks[front[k]] = r;
+            distances[front[k]] = distance;
+        }
+    }
+    (ranks, distances)
+}
+
+/// Compute the crowding distances of the members of a front.
+fn crowd(values: &[Vec<f64>], front: &[usize]) -> Vec<f64> {
+    let count = front.len();
+    let mut distances = vec![0.0; count];
+    if count == 0 {
+        return distances;
+    }
+    let columns = (0..values[front[0]].len()).map(|m| {
+        front.iter().map(|&i| values[i][m]).collect::<Vec<_>>()
+    });
+    for column in columns {
+        let mut order = (0..count).collect::<Vec<_>>();
+        order.sort_by(|&i, &j| column[i].partial_cmp(&column[j]).unwrap_or(Ordering::Equal));
+        distances[order[0]] = f64::INFINITY;
+        distances[order[count - 1]] = f64::INFINITY;
+        let range = column[order[count - 1]] - column[order[0]];
+        if range <= 0.0 || !range.is_finite() {
+            continue;
+        }
+        for k in 1..(count - 1) {
+            distances[order[k]] += (column[order[k + 1]] - column[order[k - 1]]) / range;
+        }
+    }
+    distances
+}
+
+fn compare(one: &[f64], other: &[f64]) -> Ordering {
+    for (a, b) in one.iter().zip(other.iter()) {
+        match a.partial_cmp(b) {
+            Some(Ordering::Equal) | None => {},
+            Some(ordering) => return ordering,
+        }
+    }
+    Ordering::Equal
+}
+
+#[cfg(test)]
+mod tests {
+    use communication::Communication;
+    use optimization::{Makespan, Objective, Price, Problem};
+    use platform::Platform;
+    use super::Config;
+
+    #[test]
+    fn sort() {
+        let values = vec![vec![1.0, 4.0], vec![2.0, 2.0], vec![3.0, 3.0], vec![4.0, 1.0],
+                          vec![4.0, 4.0], vec![2.0, 2.0]];
+        assert_eq!(super::sort(&values), vec![vec![0, 1, 3, 5], vec![2], vec![4]]);
+    }
+
+    #[test]
+    fn hypervolume() {
+        let values = vec![vec![1.0, 3.0], vec![2.0, 2.0], vec![3.0, 1.0], vec![5.0, 0.0]];
+        assert_eq!(super::hypervolume(&values, &[4.0, 4.0]), 6.0);
+
+        let values = vec![vec![1.0, 1.0, 1.0], vec![0.0, 0.0, 1.5]];
+        assert_eq!(super::hypervolume(&values, &[2.0, 2.0, 2.0]), 1.0 + 4.0 * 0.5 - 0.5);
+        assert_eq!(super::hypervolume(&[], &[2.0, 2.0, 2.0]), 0.0);
+    }
+
+    #[test]
+    fn explore() {
+        let content = ::parse("@GRAPH 0 {
+    TASK t0_0 TYPE 0
+    TASK t0_1 TYPE 0
+    TASK t0_2 TYPE 0
+}
+@CORE 0 {
+# price
+  1
+#----
+# type version execution_time
+  0    0       4
+}
+@CORE 1 {
+# price
+  4
+#----
+# type version execution_time
+  0    0       2
+}
+").unwrap();
+        let platform = Platform::new(&content, "CORE");
+        let communication = Communication::new(1.0);
+        let problem = Problem::new(&content.graphs[0], &platform, &communication);
+
+        let price = Price::default();
+        let objectives: Vec<&(dyn Objective + Sync)> = vec![&Makespan, &price];
+        let config = Config { seed: 5, population: 20, generations: 20, threads: 2,
+                              ..Config::default() };
+        let points = super::explore(&problem, &objectives, &config).unwrap();
+        let front = points.iter().map(|point| point.objectives.clone()).collect::<Vec<_>>();
+        assert_eq!(front, vec![vec![4.0, 5.0], vec![6.0, 4.0], vec![12.0, 1.0]]);
+        assert_eq!(super::hypervolume(&front, &[13.0, 6.0]), 2.0 * 1.0 + 6.0 * 2.0 + 1.0 * 5.0);
+    }
+}
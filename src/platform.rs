@@ -0,0 +1,160 @@
+//! Processing elements described by data tables.
+//!
+//! Each table of a particular kind, such as `@CORE`, describes a processing
+//! element. The rows of the table correspond to implementations of task types
+//! and are identified by the `type` and `version` columns.
+
+use std::collections::HashMap;
+
+use {Content, Table};
+
+/// A set of processing elements.
+pub struct Platform<'a> {
+    /// The processing elements.
+    pub elements: Vec<Element<'a>>,
+}
+
+/// A processing element.
+pub struct Element<'a> {
+    /// The table describing the processing element.
+    pub table: &'a Table,
+    rows: HashMap<(usize, usize), usize>,
+}
+
+impl<'a> Platform<'a> {
+    /// Create a platform whose processing elements are the tables called
+    /// `name`, such as `CORE`.
+    pub fn new(content: &'a Content, name: &str) -> Platform<'a> {
+        Platform {
+            elements: content.tables.iter().filter(|table| table.name == name)
+                                     .map(Element::new).collect(),
+        }
+    }
+
+    /// Return the number of processing elements.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.elements.len()
+    }
+
+    /// Check if the platform has no processing elements.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.elements.is_empty()
+    }
+}
+
+impl<'a> Element<'a> {
+    /// Create a processing element from a table.
+    ///
+    /// Rows are identified by the `type` and `version` columns. If the latter
+    /// is absent, each row is considered to be version zero.
+    pub fn new(table: &'a Table) -> Element<'a> {
+        let mut rows = HashMap::new();
+        if let Some(kinds) = find(table, "type") {
+            let count = kinds.len();
+            let versions = match find(table, "version") {
+                Some(versions) => versions.to_vec(),
+                _ => vec![0.0; count],
+            };
+            for (i, (&kind, &version)) in kinds.iter().zip(versions.iter()).enumerate() {
+                rows.entry((kind as usize, version as usize)).or_insert(i);
+            }
+        }
+        Element { table: table, rows: rows }
+    }
+
+    /// Return an attribute of the processing element such as `price`.
+    #[inline]
+    pub fn attribute(&self, name: &str) -> Option<f64> {
+        self.table.attributes.get(name).cloned()
+    }
+
+    /// Return the value of a column for a version of a task type.
+    pub fn value(&self, column: &str, kind: usize, version: usize) -> Option<f64> {
+        match (self.rows.get(&(kind, version)), find(self.table, column)) {
+            (Some(&i), Some(data)) => data.get(i).cloned(),
+            _ => None,
+        }
+    }
+
+    /// Return the execution time of a version of a task type.
+    #[inline]
+    pub fn execution_time(&self, kind: usize, version: usize) -> Option<f64> {
+        self.value("execution_time", kind, version)
+    }
+
+    /// Return the versions of a task type that the processing element can
+    /// execute in ascending order.
+    pub fn versions(&self, kind: usize) -> Vec<usize> {
+        let mut versions = self.rows.keys().filter(|&&(other, _)| other == kind)
+                                           .map(|&(_, version)| version)
+                                           .collect::<Vec<_>>();
+        versions.sort();
+        versions
+    }
+
+    /// Return the version of a task type with the shortest execution time
+    /// together with the time.
+    pub fn fastest(&self, kind: usize) -> Option<(usize, f64)> {
+        let mut best: Option<(usize, f64)> = None;
+        for version in self.versions(kind) {
+            if let Some(time) = self.execution_time(kind, version) {
+                match best {
+                    Some((_, other)) if other <= time => {},
+                    _ => best = Some((version, time)),
+                }
+            }
+        }
+        best
+    }
+}
+
+fn find<'a>(table: &'a Table, name: &str) -> Option<&'a [f64]> {
+    table.columns.iter().find(|column| column.name == name).map(|column| &column.data[..])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Platform;
+
+    const INPUT: &str = "
+@CORE 0 {
+# price
+  2.5
+#----
+# type version execution_time
+  0    0       3
+  0    1       2
+  1    0       4
+}
+@COMMUN 0 {
+# \n#----\n# type volume\n 0 4\n}
+@CORE 1 {
+# price
+  1.5
+#----
+# type execution_time
+  1    5
+}
+";
+
+    #[test]
+    fn new() {
+        let content = ::parse(INPUT.trim()).unwrap();
+        let platform = Platform::new(&content, "CORE");
+        assert_eq!(platform.len(), 2);
+
+        let ref element = platform.elements[0];
+        assert_eq!(element.attribute("price"), Some(2.5));
+        assert_eq!(element.versions(0), vec![0, 1]);
+        assert_eq!(element.execution_time(0, 1), Some(2.0));
+        assert_eq!(element.execution_time(1, 1), None);
+        assert_eq!(element.fastest(0), Some((1, 2.0)));
+
+        let ref element = platform.elements[1];
+        assert_eq!(element.versions(0), Vec::<usize>::new());
+        assert_eq!(element.execution_time(1, 0), Some(5.0));
+        assert_eq!(element.fastest(0), None);
+    }
+}
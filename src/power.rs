@@ -0,0 +1,222 @@
+//! Power profiles of schedules.
+//!
+//! A power profile is a matrix whose rows correspond to processing elements
+//! and columns to time steps. The matrix is stored in column-major order, so
+//! that the power of all processing elements at a particular time step is a
+//! contiguous slice.
+
+use std::collections::HashMap;
+use std::iter::{Skip, StepBy};
+use std::slice::{Chunks, Iter};
+
+use Graph;
+use energy;
+use platform::Platform;
+use schedule::Schedule;
+
+/// A configuration of power profiling.
+#[derive(Clone, Debug)]
+pub struct Config {
+    /// The name of the column with dynamic power.
+    pub dynamic_power: String,
+    /// The name of the attribute with static power, if any.
+    pub static_power: Option<String>,
+    /// The sampling interval.
+    pub interval: f64,
+    /// The number of hyperperiods to cover.
+    pub repetitions: usize,
+}
+
+/// A power profile.
+#[derive(Clone, Debug)]
+pub struct Profile {
+    /// The number of processing elements.
+    pub elements: usize,
+    /// The number of time steps.
+    pub steps: usize,
+    /// The sampling interval.
+    pub interval: f64,
+    /// The power values in column-major order.
+    pub data: Vec<f64>,
+}
+
+impl Default for Config {
+    fn default() -> Config {
+        Config {
+            dynamic_power: String::from("dynamic_power"),
+            static_power: None,
+            interval: 1e-3,
+            repetitions: 1,
+        }
+    }
+}
+
+impl Profile {
+    /// Return the power of a processing element at a time step.
+    #[inline]
+    pub fn get(&self, element: usize, step: usize) -> f64 {
+        self.data[step * self.elements + element]
+    }
+
+    /// Iterate over the processing elements.
+    ///
+    /// Each item iterates over the power of a processing element at all time
+    /// steps.
+    pub fn rows(&self) -> impl Iterator<Item = StepBy<Skip<Iter<'_, f64>>>> + '_ {
+        let stride = if self.elements > 0 { self.elements } else { 1 };
+        (0..self.elements).map(move |element| self.data.iter().skip(element).step_by(stride))
+    }
+
+    /// Iterate over the time steps.
+    ///
+    /// Each item is the power of all processing elements at a time step.
+    #[inline]
+    pub fn steps(&self) -> Chunks<'_, f64> {
+        self.data.chunks(if self.elements > 0 { self.elements } else { 1 })
+    }
+}
+
+/// Compute the power profile of a schedule.
+///
+/// The schedule is assumed to be repeated every `PERIOD` of the graph within
+/// the hyperperiod, and the graph is executed once per hyperperiod if it has no
+/// period. The repetitions are counted as in `energy::estimate`. Each sample
+/// is the average power over the corresponding interval. The function returns
+/// `None` if the dynamic power of a scheduled task is unknown or if the
+/// sampling interval is not positive.
+pub fn profile(schedule: &Schedule, graph: &Graph, platform: &Platform, hyperperiod: f64,
+               config: &Config) -> Option<Profile> {
+
+    if config.interval <= 0.0 || config.interval.is_nan() {
+        return None;
+    }
+
+    let period = match graph.attributes.get("PERIOD") {
+        Some(&period) if period > 0 => period as f64,
+        _ => hyperperiod,
+    };
+    let instances = energy::instances(graph, hyperperiod);
+
+    let elements = platform.len();
+    let interval = config.interval;
+    let span = hyperperiod * config.repetitions as f64;
+    let steps = (span / interval - 1e-9).ceil().max(0.0) as usize;
+
+    let mut data = vec![0.0; elements * steps];
+
+    if let Some(ref name) = config.static_power {
+        for (i, element) in platform.elements.iter().enumerate() {
+            if let Some(power) = element.attribute(name) {
+                for k in 0..steps {
+                    data[k * elements + i] += power;
+                }
+            }
+        }
+    }
+
+    let mut kinds = HashMap::new();
+    for task in graph.tasks.iter() {
+        kinds.insert(task.id, task.kind);
+    }
+
+    for entry in schedule.entries.iter() {
+        let power = match (kinds.get(&entry.task), platform.elements.get(entry.element)) {
+            (Some(&kind), Some(element)) => {
+                element.value(&config.dynamic_power, kind, entry.version)
+            },
+            _ => None,
+        };
+        let power = match power {
+            Some(power) => power,
+            _ => return None,
+        };
+        for r in 0..(config.repetitions * instances) {
+            let offset = (r / instances) as f64 * hyperperiod + (r % instances) as f64 * period;
+            let (start, finish) = (offset + entry.start, offset + entry.finish);
+            let first = (start / interval).floor().max(0.0) as usize;
+            for k in first..steps {
+                let (begin, end) = (k as f64 * interval, (k + 1) as f64 * interval);
+                if begin >= finish {
+                    break;
+                }
+                let overlap = finish.min(end) - start.max(begin);
+                if overlap > 0.0 {
+                    data[k * elements + entry.element] += power * overlap / interval;
+                }
+            }
+        }
+    }
+
+    Some(Profile { elements: elements, steps: steps, interval: interval, data: data })
+}
+
+#[cfg(test)]
+mod tests {
+    use platform::Platform;
+    use schedule::{Entry, Schedule};
+    use super::Config;
+
+    const INPUT: &str = "@GRAPH 0 {
+    PERIOD 4
+    TASK t0_0 TYPE 0
+    TASK t0_1 TYPE 0
+}
+@CORE 0 {
+# static_power
+  0.5
+#----
+# type version dynamic_power execution_time
+  0    0       2             1.5
+}
+@CORE 1 {
+# price
+  1
+#----
+# type version dynamic_power execution_time
+  0    0       4             1.5
+}
+";
+
+    #[test]
+    fn profile() {
+        let content = ::parse(INPUT).unwrap();
+        let platform = Platform::new(&content, "CORE");
+        let schedule = Schedule { entries: vec![
+            Entry { task: 0, element: 0, version: 0, start: 0.0, finish: 1.5 },
+            Entry { task: 1, element: 1, version: 0, start: 1.5, finish: 3.0 },
+        ]};
+        let config = Config {
+            static_power: Some(String::from("static_power")),
+            interval: 1.0,
+            repetitions: 2,
+            ..Config::default()
+        };
+
+        let profile = super::profile(&schedule, &content.graphs[0], &platform, 8.0,
+                                     &config).unwrap();
+        assert_eq!(profile.steps, 16);
+        assert_eq!(profile.steps().count(), 16);
+        assert_eq!(profile.rows().count(), 2);
+
+        let expected = vec![
+            2.5, 0.0, 1.5, 2.0, 0.5, 4.0, 0.5, 0.0,
+            2.5, 0.0, 1.5, 2.0, 0.5, 4.0, 0.5, 0.0,
+        ];
+        for (step, values) in profile.steps().enumerate() {
+            assert_eq!(values[0], expected[(2 * step) % 16]);
+            assert_eq!(values[1], expected[(2 * step + 1) % 16]);
+            assert_eq!(profile.get(1, step), values[1]);
+        }
+        for (element, values) in profile.rows().enumerate() {
+            let values = values.cloned().collect::<Vec<_>>();
+            assert_eq!(values.len(), 16);
+            for (step, &value) in values.iter().enumerate() {
+                assert_eq!(value, profile.get(element, step));
+            }
+        }
+
+        let config = Config { interval: 0.0, ..Config::default() };
+        assert!(super::profile(&schedule, &content.graphs[0], &platform, 8.0,
+                               &config).is_none());
+    }
+}
@@ -0,0 +1,78 @@
+/// A deterministic pseudorandom number generator.
+///
+/// The generator implements the SplitMix64 algorithm, which is fast, has a
+/// period of 2^64, and produces the same sequence for the same seed on every
+/// platform.
+#[derive(Clone, Debug)]
+pub struct Generator {
+    state: u64,
+}
+
+impl Generator {
+    /// Create a generator.
+    #[inline]
+    pub fn new(seed: u64) -> Generator {
+        Generator { state: seed }
+    }
+
+    /// Return the next integer.
+    pub fn next(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E3779B97F4A7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+        z ^ (z >> 31)
+    }
+
+    /// Return a number uniformly distributed in `[0, 1)`.
+    #[inline]
+    pub fn uniform(&mut self) -> f64 {
+        (self.next() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// Return an integer uniformly distributed in `[0, bound)`.
+    #[inline]
+    pub fn below(&mut self, bound: usize) -> usize {
+        (self.uniform() * bound as f64) as usize
+    }
+
+    /// Shuffle a slice in place.
+    pub fn shuffle<T>(&mut self, data: &mut [T]) {
+        for i in (1..data.len()).rev() {
+            let j = self.below(i + 1);
+            data.swap(i, j);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Generator;
+
+    #[test]
+    fn next() {
+        let mut one = Generator::new(42);
+        let mut other = Generator::new(42);
+        for _ in 0..100 {
+            assert_eq!(one.next(), other.next());
+        }
+        assert_eq!(Generator::new(0).next(), 0xE220A8397B1DCDAF);
+    }
+
+    #[test]
+    fn below() {
+        let mut generator = Generator::new(1);
+        for _ in 0..1000 {
+            assert!(generator.below(7) < 7);
+        }
+    }
+
+    #[test]
+    fn shuffle() {
+        let mut data = (0..10).collect::<Vec<_>>();
+        Generator::new(1).shuffle(&mut data);
+        assert!(data != (0..10).collect::<Vec<_>>());
+        data.sort();
+        assert_eq!(data, (0..10).collect::<Vec<_>>());
+    }
+}
@@ -0,0 +1,433 @@
+//! Schedulability analysis.
+//!
+//! Each task is treated as a periodic task whose period is equal to the
+//! `PERIOD` of its graph and whose worst-case execution time is taken from the
+//! table of the processing element it is mapped onto.
+
+use std::collections::HashMap;
+
+use Content;
+use communication::Communication;
+use mapping::Mapping;
+use platform::Platform;
+
+const MAX_ITERATIONS: usize = 1000;
+
+/// A schedulability test.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Test {
+    /// The Liu–Layland bound for rate-monotonic scheduling.
+    RateMonotonic,
+    /// The exact bound for earliest-deadline-first scheduling.
+    EarliestDeadlineFirst,
+    /// The hyperbolic bound for rate-monotonic scheduling.
+    Hyperbolic,
+}
+
+/// The outcome of a schedulability test.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Outcome {
+    /// The test.
+    pub test: Test,
+    /// The verdict.
+    pub passed: bool,
+    /// The difference between the bound and the tested quantity, which is
+    /// negative when the test fails.
+    pub margin: f64,
+}
+
+/// The schedulability of a processing element.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Report {
+    /// The index of the processing element.
+    pub element: usize,
+    /// The number of tasks mapped onto the processing element.
+    pub tasks: usize,
+    /// The total utilization.
+    pub utilization: f64,
+    /// The outcomes of the tests.
+    pub outcomes: Vec<Outcome>,
+}
+
+/// The worst-case response time of a task.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Response {
+    /// The position of the graph in the content.
+    pub graph: usize,
+    /// The ID of the task.
+    pub task: usize,
+    /// The release jitter inherited from the predecessors.
+    pub jitter: f64,
+    /// The worst-case response time counted from the release of the graph,
+    /// which is infinite if it cannot be bounded.
+    pub time: f64,
+    /// The hard deadline of the task, if any.
+    pub deadline: Option<f64>,
+}
+
+impl Response {
+    /// Check if the hard deadline, if any, is met.
+    #[inline]
+    pub fn met(&self) -> bool {
+        match self.deadline {
+            Some(deadline) => self.time <= deadline,
+            _ => self.time.is_finite(),
+        }
+    }
+}
+
+impl Report {
+    /// Find the outcome of a test.
+    pub fn outcome(&self, test: Test) -> Option<&Outcome> {
+        self.outcomes.iter().find(|outcome| outcome.test == test)
+    }
+}
+
+/// Run the utilization-based tests on each processing element.
+///
+/// The relative deadlines of the tasks are assumed to be equal to their
+/// periods, and precedence constraints are ignored.
+///
+/// The mappings correspond to the graphs of the content in the same order. The
+/// function returns `None` if the numbers of graphs and mappings differ, if a
+/// graph has no period, if a mapping does not assign every task of its graph
+/// exactly once or refers to an unknown task or processing element, or if an
+/// execution time is unknown.
+pub fn analyze(content: &Content, platform: &Platform, mappings: &[Mapping])
+               -> Option<Vec<Report>> {
+
+    if content.graphs.len() != mappings.len() {
+        return None;
+    }
+    let mut utilizations = vec![vec![]; platform.len()];
+    for (graph, mapping) in content.graphs.iter().zip(mappings.iter()) {
+        let period = match graph.attributes.get("PERIOD") {
+            Some(&period) if period > 0 => period as f64,
+            _ => return None,
+        };
+        let mut kinds = HashMap::new();
+        for task in graph.tasks.iter() {
+            mapping.find(task.id)?;
+            kinds.insert(task.id, task.kind);
+        }
+        for assignment in mapping.assignments.iter() {
+            let element = platform.elements.get(assignment.element);
+            let time = match (kinds.remove(&assignment.task), element) {
+                (Some(kind), Some(element)) => element.execution_time(kind, assignment.version),
+                _ => None,
+            };
+            match time {
+                Some(time) => utilizations[assignment.element].push(time / period),
+                _ => return None,
+            }
+        }
+    }
+
+    Some(utilizations.iter().enumerate().map(|(element, utilizations)| {
+        let count = utilizations.len();
+        let total = utilizations.iter().fold(0.0, |sum, &value| sum + value);
+        let product = utilizations.iter().fold(1.0, |product, &value| product * (value + 1.0));
+        let bound = if count == 0 {
+            1.0
+        } else {
+            count as f64 * (2f64.powf(1.0 / count as f64) - 1.0)
+        };
+        Report {
+            element: element,
+            tasks: count,
+            utilization: total,
+            outcomes: vec![
+                outcome(Test::RateMonotonic, bound - total),
+                outcome(Test::EarliestDeadlineFirst, 1.0 - total),
+                outcome(Test::Hyperbolic, 2.0 - product),
+            ],
+        }
+    }).collect())
+}
+
+/// Compute the worst-case response times of the tasks under preemptive
+/// fixed-priority scheduling on each processing element.
+///
+/// The mappings correspond to the graphs of the content in the same order. A
+/// task is released together with its graph but cannot start before the data
+/// of its predecessors arrive, which is modeled as release jitter equal to the
+/// largest response time of a predecessor plus the transfer time of the
+/// connecting arc. Response times and jitters are refined iteratively until
+/// they converge. Tasks with equal priorities are assumed to interfere with
+/// each other. The function returns `None` under the same circumstances as
+/// `analyze` and also if a graph contains a cycle.
+pub fn response(content: &Content, platform: &Platform, communication: &Communication,
+                mappings: &[Mapping]) -> Option<Vec<Response>> {
+
+    struct Item {
+        element: usize,
+        priority: usize,
+        period: f64,
+        time: f64,
+        predecessors: Vec<(usize, f64)>,
+    }
+
+    if content.graphs.len() != mappings.len() {
+        return None;
+    }
+    let mut items = vec![];
+    let mut responses = vec![];
+    for (g, (graph, mapping)) in content.graphs.iter().zip(mappings.iter()).enumerate() {
+        let period = match graph.attributes.get("PERIOD") {
+            Some(&period) if period > 0 => period as f64,
+            _ => return None,
+        };
+        let dag = match ::dag::new(graph) {
+            Some(dag) => dag,
+            _ => return None,
+        };
+        if mapping.assignments.len() != graph.tasks.len() {
+            return None;
+        }
+        let offset = items.len();
+        for task in graph.tasks.iter() {
+            let assignment = match mapping.find(task.id) {
+                Some(assignment) => assignment,
+                _ => return None,
+            };
+            let time = match platform.elements.get(assignment.element) {
+                Some(element) => element.execution_time(task.kind, assignment.version),
+                _ => None,
+            };
+            let time = match time {
+                Some(time) => time,
+                _ => return None,
+            };
+            items.push(Item {
+                element: assignment.element,
+                priority: assignment.priority,
+                period: period,
+                time: time,
+                predecessors: vec![],
+            });
+            let deadline = graph.deadlines.iter().filter(|deadline| deadline.on == task.id)
+                                                 .map(|deadline| deadline.at as f64)
+                                                 .fold(None, |result: Option<f64>, at| {
+                                                     Some(result.map_or(at, |other| other.min(at)))
+                                                 });
+            responses.push(Response {
+                graph: g,
+                task: task.id,
+                jitter: 0.0,
+                time: 0.0,
+                deadline: deadline,
+            });
+        }
+        for (i, predecessors) in dag.predecessors.iter().enumerate() {
+            for &(j, k) in predecessors.iter() {
+                let delay = communication.cost(&graph.arcs[k], items[offset + j].element,
+                                               items[offset + i].element);
+                items[offset + i].predecessors.push((offset + j, delay));
+            }
+        }
+    }
+
+    let count = items.len();
+    let mut converged = false;
+    for _ in 0..MAX_ITERATIONS {
+        for i in 0..count {
+            let ref item = items[i];
+            let interference = (0..count).filter(|&j| {
+                j != i && items[j].element == item.element && items[j].priority <= item.priority
+            }).collect::<Vec<_>>();
+            let utilization = interference.iter().fold(0.0, |sum, &j| {
+                sum + items[j].time / items[j].period
+            });
+            if utilization >= 1.0 || !responses[i].jitter.is_finite() {
+                responses[i].time = f64::INFINITY;
+                continue;
+            }
+            let mut busy = item.time;
+            loop {
+                let next = interference.iter().fold(item.time, |sum, &j| {
+                    let jitter = responses[j].jitter;
+                    if jitter.is_finite() {
+                        sum + ((busy + jitter) / items[j].period).ceil() * items[j].time
+                    } else {
+                        f64::INFINITY
+                    }
+                });
+                if !next.is_finite() || next <= busy {
+                    busy = next;
+                    break;
+                }
+                busy = next;
+            }
+            responses[i].time = responses[i].jitter + busy;
+        }
+
+        let mut changed = false;
+        for i in 0..count {
+            let jitter = items[i].predecessors.iter().fold(0.0, |jitter: f64, &(j, delay)| {
+                jitter.max(responses[j].time + delay)
+            });
+            if jitter != responses[i].jitter {
+                responses[i].jitter = jitter;
+                changed = true;
+            }
+        }
+        if !changed {
+            converged = true;
+            break;
+        }
+    }
+    if !converged {
+        for response in responses.iter_mut() {
+            response.time = f64::INFINITY;
+        }
+    }
+
+    Some(responses)
+}
+
+#[inline]
+fn outcome(test: Test, margin: f64) -> Outcome {
+    Outcome { test: test, passed: margin >= 0.0, margin: margin }
+}
+
+#[cfg(test)]
+mod tests {
+    use communication::Communication;
+    use mapping::{Assignment, Mapping};
+    use platform::Platform;
+    use super::Test;
+
+    const INPUT: &str = "@GRAPH 0 {
+    PERIOD 10
+    TASK t0_0 TYPE 0
+    TASK t0_1 TYPE 1
+}
+@GRAPH 1 {
+    PERIOD 20
+    TASK t1_0 TYPE 1
+}
+@CORE 0 {
+# price
+  1
+#----
+# type version execution_time
+  0    0       3
+  1    0       4
+}
+";
+
+    #[test]
+    fn analyze() {
+        let content = ::parse(INPUT).unwrap();
+        let platform = Platform::new(&content, "CORE");
+
+        macro_rules! mapping(
+            ($($task:expr),*) => (Mapping { assignments: vec![$(
+                Assignment { task: $task, element: 0, version: 0, priority: 0 }
+            ),*] });
+        );
+
+        let reports = super::analyze(&content, &platform, &[mapping!(0, 1), mapping!(0)]).unwrap();
+        let ref report = reports[0];
+        assert_eq!(report.tasks, 3);
+        assert!((report.utilization - 0.9).abs() < 1e-12);
+
+        let outcome = report.outcome(Test::RateMonotonic).unwrap();
+        assert!(!outcome.passed);
+        assert!((outcome.margin - (3.0 * (2f64.powf(1.0 / 3.0) - 1.0) - 0.9)).abs() < 1e-12);
+
+        let outcome = report.outcome(Test::EarliestDeadlineFirst).unwrap();
+        assert!(outcome.passed);
+        assert!((outcome.margin - 0.1).abs() < 1e-12);
+
+        let outcome = report.outcome(Test::Hyperbolic).unwrap();
+        assert!(!outcome.passed);
+        assert!((outcome.margin - (2.0 - 1.3 * 1.4 * 1.2)).abs() < 1e-12);
+
+        let mut light = content.clone();
+        light.graphs.truncate(1);
+        let reports = super::analyze(&light, &platform, &[mapping!(0, 1)]).unwrap();
+        assert!(reports[0].outcomes.iter().all(|outcome| outcome.passed));
+
+        assert!(super::analyze(&content, &platform, &[mapping!(0, 1)]).is_none());
+        assert!(super::analyze(&content, &platform, &[mapping!(0), mapping!(0)]).is_none());
+        assert!(super::analyze(&content, &platform, &[mapping!(0, 1, 2), mapping!(0)]).is_none());
+        assert!(super::analyze(&content, &platform, &[mapping!(0, 1, 1), mapping!(0)]).is_none());
+    }
+
+    #[test]
+    fn response() {
+        let content = ::parse("@GRAPH 0 {
+    PERIOD 10
+    TASK t0_0 TYPE 0
+    TASK t0_1 TYPE 0
+    TASK t0_2 TYPE 1
+    ARC a0_0 FROM t0_0 TO t0_1 TYPE 0
+    HARD_DEADLINE d0_0 ON t0_1 AT 6
+}
+@GRAPH 1 {
+    PERIOD 5
+    TASK t1_0 TYPE 1
+    HARD_DEADLINE d1_0 ON t1_0 AT 5
+}
+@CORE 0 {
+# price
+  1
+#----
+# type version execution_time
+  0    0       2
+  1    0       1
+}
+@CORE 1 {
+# price
+  1
+#----
+# type version execution_time
+  0    0       2
+  1    0       1
+}
+").unwrap();
+        let platform = Platform::new(&content, "CORE");
+        let communication = Communication::new(1.0);
+
+        macro_rules! assignment(
+            ($task:expr, $element:expr, $priority:expr) => (
+                Assignment { task: $task, element: $element, version: 0, priority: $priority }
+            );
+        );
+
+        let mappings = vec![
+            Mapping { assignments: vec![
+                assignment!(0, 0, 1), assignment!(1, 1, 2), assignment!(2, 0, 3),
+            ]},
+            Mapping { assignments: vec![assignment!(0, 0, 0)] },
+        ];
+        let responses = super::response(&content, &platform, &communication,
+                                        &mappings).unwrap();
+
+        let times = responses.iter().map(|response| response.time).collect::<Vec<_>>();
+        assert_eq!(times, vec![3.0, 6.0, 4.0, 1.0]);
+        assert_eq!(responses[1].jitter, 4.0);
+        assert!(responses.iter().all(|response| response.met()));
+        assert!(super::response(&content, &platform, &communication, &mappings[..1]).is_none());
+
+        let mut duplicate = mappings.clone();
+        duplicate[1].assignments.push(assignment!(0, 1, 1));
+        assert!(super::response(&content, &platform, &communication, &duplicate).is_none());
+
+        let mappings = vec![
+            Mapping { assignments: vec![
+                assignment!(0, 0, 1), assignment!(1, 0, 2), assignment!(2, 0, 3),
+            ]},
+            Mapping { assignments: vec![assignment!(0, 0, 4)] },
+        ];
+        let responses = super::response(&content, &platform, &communication,
+                                        &mappings).unwrap();
+
+        let times = responses.iter().map(|response| response.time).collect::<Vec<_>>();
+        assert_eq!(times, vec![2.0, 6.0, 5.0, 6.0]);
+        assert_eq!(responses[1].jitter, 2.0);
+        assert!(responses[1].met());
+        assert!(!responses[3].met());
+    }
+}
@@ -0,0 +1,524 @@
+//! Scheduling of task graphs onto processing elements.
+
+use std::cmp::Ordering;
+use std::collections::HashMap;
+
+use Graph;
+use communication::Communication;
+use mapping::Mapping;
+use platform::Platform;
+
+/// A schedule of a task graph.
+#[derive(Clone, Debug)]
+pub struct Schedule {
+    /// The placement of each task.
+    pub entries: Vec<Entry>,
+}
+
+/// The placement of a task in a schedule.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Entry {
+    /// The ID of the task.
+    pub task: usize,
+    /// The index of the processing element.
+    pub element: usize,
+    /// The version of the task type.
+    pub version: usize,
+    /// The start time.
+    pub start: f64,
+    /// The finish time.
+    pub finish: f64,
+}
+
+/// A violation of a constraint found in a schedule.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Violation {
+    /// A task of the graph is not scheduled.
+    Missing { task: usize },
+    /// A task is scheduled more than once or is not part of the graph.
+    Unexpected { task: usize },
+    /// A task is placed onto a processing element that does not exist or
+    /// cannot execute the chosen version.
+    Unsupported { task: usize, element: usize, version: usize },
+    /// The duration of a task differs from its execution time.
+    Duration { task: usize, expected: f64, actual: f64 },
+    /// A task starts before it is released.
+    Release { task: usize, release: f64, start: f64 },
+    /// A task starts before the data of an incoming arc arrive.
+    Precedence { arc: usize, ready: f64, start: f64 },
+    /// Two tasks overlap on a processing element.
+    Overlap { element: usize, first: usize, second: usize },
+    /// A task finishes after its hard deadline.
+    Deadline { deadline: usize, task: usize, at: f64, finish: f64 },
+}
+
+impl Schedule {
+    /// Find the entry of a task given its ID.
+    pub fn find(&self, task: usize) -> Option<&Entry> {
+        self.entries.iter().find(|entry| entry.task == task)
+    }
+
+    /// Return the finish time of the last task.
+    pub fn makespan(&self) -> f64 {
+        self.entries.iter().fold(0.0, |makespan, entry| entry.finish.max(makespan))
+    }
+}
+
+/// Schedule a task graph using the heterogeneous earliest-finish-time (HEFT)
+/// algorithm.
+///
+/// Tasks are prioritized by their upward ranks, which are computed using the
+/// execution times averaged over the processing elements and the transfer
+/// times of the arcs. Each task is then placed, in the order of decreasing
+/// rank, onto the processing element that finishes it the earliest, possibly
+/// in an idle slot between two already scheduled tasks. The fastest version of
+/// a task type is used on each processing element.
+///
+/// The function returns `None` if the graph is not acyclic or if some task
+/// cannot be executed by any processing element.
+pub fn heft(graph: &Graph, platform: &Platform, communication: &Communication)
+            -> Option<Schedule> {
+
+    let dag = match ::dag::new(graph) {
+        Some(dag) => dag,
+        _ => return None,
+    };
+    let (count, elements) = (graph.tasks.len(), platform.len());
+
+    let mut times = vec![vec![None; elements]; count];
+    let mut average = vec![0.0; count];
+    for (i, task) in graph.tasks.iter().enumerate() {
+        let mut total = 0.0;
+        let mut supported = 0;
+        for (j, element) in platform.elements.iter().enumerate() {
+            if let Some((version, time)) = element.fastest(task.kind) {
+                times[i][j] = Some((version, time));
+                total += time;
+                supported += 1;
+            }
+        }
+        if supported == 0 {
+            return None;
+        }
+        average[i] = total / supported as f64;
+    }
+
+    let mut rank = vec![0.0; count];
+    for &i in dag.order.iter().rev() {
+        let mut longest: f64 = 0.0;
+        for &(j, k) in dag.successors[i].iter() {
+            longest = longest.max(communication.time(graph.arcs[k].kind) + rank[j]);
+        }
+        rank[i] = average[i] + longest;
+    }
+
+    let mut position = vec![0; count];
+    for (p, &i) in dag.order.iter().enumerate() {
+        position[i] = p;
+    }
+    let mut queue = (0..count).collect::<Vec<_>>();
+    queue.sort_by(|&i, &j| {
+        match rank[j].partial_cmp(&rank[i]) {
+            Some(Ordering::Equal) | None => position[i].cmp(&position[j]),
+            Some(ordering) => ordering,
+        }
+    });
+
+    let mut slots: Vec<Vec<(f64, f64)>> = vec![vec![]; elements];
+    let mut placed: Vec<Option<Entry>> = vec![None; count];
+    for &i in queue.iter() {
+        let mut best: Option<Entry> = None;
+        for j in 0..elements {
+            let (version, duration) = match times[i][j] {
+                Some(time) => time,
+                _ => continue,
+            };
+            let mut ready: f64 = 0.0;
+            for &(p, k) in dag.predecessors[i].iter() {
+                let entry = placed[p].unwrap();
+                let delay = communication.cost(&graph.arcs[k], entry.element, j);
+                ready = ready.max(entry.finish + delay);
+            }
+            let start = insert(&slots[j], ready, duration);
+            match best {
+                Some(ref entry) if entry.finish <= start + duration => {},
+                _ => best = Some(Entry {
+                    task: graph.tasks[i].id,
+                    element: j,
+                    version: version,
+                    start: start,
+                    finish: start + duration,
+                }),
+            }
+        }
+        let entry = best.unwrap();
+        let slots = &mut slots[entry.element];
+        let k = slots.iter().position(|&(start, _)| start > entry.start).unwrap_or(slots.len());
+        slots.insert(k, (entry.start, entry.finish));
+        placed[i] = Some(entry);
+    }
+
+    Some(Schedule { entries: placed.into_iter().map(|entry| entry.unwrap()).collect() })
+}
+
+/// Schedule a task graph according to a mapping.
+///
+/// Among the tasks whose predecessors have already been scheduled, the one
+/// with the highest priority is placed onto its processing element at the
+/// earliest time the data of its predecessors are available, possibly in an
+/// idle slot between two already scheduled tasks.
+///
+/// The function returns `None` if the graph is not acyclic, if a task is not
+/// mapped, or if a processing element cannot execute the version of a task
+/// assigned to it.
+pub fn list(graph: &Graph, platform: &Platform, communication: &Communication,
+            mapping: &Mapping) -> Option<Schedule> {
+
+    let dag = match ::dag::new(graph) {
+        Some(dag) => dag,
+        _ => return None,
+    };
+    let count = graph.tasks.len();
+
+    let mut assignments = Vec::with_capacity(count);
+    let mut durations = Vec::with_capacity(count);
+    for task in graph.tasks.iter() {
+        let assignment = match mapping.find(task.id) {
+            Some(assignment) => assignment,
+            _ => return None,
+        };
+        let time = platform.elements.get(assignment.element).and_then(|element| {
+            element.execution_time(task.kind, assignment.version)
+        });
+        match time {
+            Some(time) => durations.push(time),
+            _ => return None,
+        }
+        assignments.push(assignment);
+    }
+
+    let mut degrees = dag.predecessors.iter().map(|arcs| arcs.len()).collect::<Vec<_>>();
+    let mut ready = (0..count).filter(|&i| degrees[i] == 0).collect::<Vec<_>>();
+    let mut slots: Vec<Vec<(f64, f64)>> = vec![vec![]; platform.len()];
+    let mut placed: Vec<Option<Entry>> = vec![None; count];
+    while !ready.is_empty() {
+        let k = (0..ready.len()).min_by_key(|&k| (assignments[ready[k]].priority, ready[k])).unwrap();
+        let i = ready.swap_remove(k);
+        let assignment = assignments[i];
+
+        let mut time: f64 = 0.0;
+        for &(p, k) in dag.predecessors[i].iter() {
+            let entry = placed[p].unwrap();
+            let delay = communication.cost(&graph.arcs[k], entry.element, assignment.element);
+            time = time.max(entry.finish + delay);
+        }
+        let ref mut slots = slots[assignment.element];
+        let start = insert(slots, time, durations[i]);
+        let k = slots.iter().position(|&(other, _)| other > start).unwrap_or(slots.len());
+        slots.insert(k, (start, start + durations[i]));
+        placed[i] = Some(Entry {
+            task: graph.tasks[i].id,
+            element: assignment.element,
+            version: assignment.version,
+            start: start,
+            finish: start + durations[i],
+        });
+
+        for &(j, _) in dag.successors[i].iter() {
+            degrees[j] -= 1;
+            if degrees[j] == 0 {
+                ready.push(j);
+            }
+        }
+    }
+
+    Some(Schedule { entries: placed.into_iter().map(|entry| entry.unwrap()).collect() })
+}
+
+/// Check a schedule of a task graph.
+///
+/// The graph is assumed to be released at time zero, which is also the time
+/// the deadlines are counted from. Individual tasks can be released later, in
+/// which case their release times are given by task ID in `releases`, and the
+/// tasks not listed there are released at time zero. Execution times are
+/// taken from the processing elements and delays from the communication
+/// model. All the violations found are returned, and an empty vector means
+/// that the schedule is feasible.
+pub fn check(schedule: &Schedule, graph: &Graph, platform: &Platform,
+             communication: &Communication, releases: &HashMap<usize, f64>)
+             -> Vec<Violation> {
+
+    let mut violations = vec![];
+
+    let mut entries = HashMap::new();
+    for entry in schedule.entries.iter() {
+        let known = graph.tasks.iter().any(|task| task.id == entry.task);
+        if !known || entries.contains_key(&entry.task) {
+            violations.push(Violation::Unexpected { task: entry.task });
+            continue;
+        }
+        entries.insert(entry.task, entry);
+    }
+    for task in graph.tasks.iter() {
+        let entry = match entries.get(&task.id) {
+            Some(entry) => entry,
+            _ => {
+                violations.push(Violation::Missing { task: task.id });
+                continue;
+            },
+        };
+        let time = platform.elements.get(entry.element).and_then(|element| {
+            element.execution_time(task.kind, entry.version)
+        });
+        match time {
+            Some(time) => if !equal(entry.finish - entry.start, time) {
+                violations.push(Violation::Duration {
+                    task: task.id,
+                    expected: time,
+                    actual: entry.finish - entry.start,
+                });
+            },
+            _ => violations.push(Violation::Unsupported {
+                task: task.id,
+                element: entry.element,
+                version: entry.version,
+            }),
+        }
+        let release = releases.get(&task.id).cloned().unwrap_or(0.0);
+        if !after(entry.start, release) {
+            violations.push(Violation::Release {
+                task: task.id,
+                release: release,
+                start: entry.start,
+            });
+        }
+    }
+
+    for arc in graph.arcs.iter() {
+        if let (Some(from), Some(to)) = (entries.get(&arc.from), entries.get(&arc.to)) {
+            let ready = from.finish + communication.cost(arc, from.element, to.element);
+            if !after(to.start, ready) {
+                violations.push(Violation::Precedence { arc: arc.id, ready: ready, start: to.start });
+            }
+        }
+    }
+
+    let mut placed = graph.tasks.iter().filter_map(|task| entries.get(&task.id))
+                                       .collect::<Vec<_>>();
+    placed.sort_by(|one, other| {
+        match one.element.cmp(&other.element) {
+            Ordering::Equal => one.start.partial_cmp(&other.start).unwrap_or(Ordering::Equal),
+            ordering => ordering,
+        }
+    });
+    for (i, first) in placed.iter().enumerate() {
+        for second in placed[(i + 1)..].iter() {
+            if second.element != first.element || after(second.start, first.finish) {
+                break;
+            }
+            violations.push(Violation::Overlap {
+                element: first.element,
+                first: first.task,
+                second: second.task,
+            });
+        }
+    }
+
+    for deadline in graph.deadlines.iter() {
+        if let Some(entry) = entries.get(&deadline.on) {
+            let at = deadline.at as f64;
+            if !after(at, entry.finish) {
+                violations.push(Violation::Deadline {
+                    deadline: deadline.id,
+                    task: deadline.on,
+                    at: at,
+                    finish: entry.finish,
+                });
+            }
+        }
+    }
+
+    violations
+}
+
+/// Find the earliest start time of an interval of a particular duration that
+/// fits between busy slots, which are sorted by their start times.
+fn insert(slots: &[(f64, f64)], ready: f64, duration: f64) -> f64 {
+    let mut start = ready;
+    for &(begin, end) in slots.iter() {
+        if start + duration <= begin {
+            break;
+        }
+        start = start.max(end);
+    }
+    start
+}
+
+const EPSILON: f64 = 1e-9;
+
+#[inline]
+fn after(one: f64, other: f64) -> bool {
+    one - other >= -EPSILON * other.abs().max(1.0)
+}
+
+#[inline]
+fn equal(one: f64, other: f64) -> bool {
+    after(one, other) && after(other, one)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use communication::Communication;
+    use mapping::Mapping;
+    use platform::Platform;
+    use super::{Entry, Schedule, Violation};
+
+    const INPUT: &str = "@GRAPH 0 {
+    HARD_DEADLINE d0_0 ON t0_3 AT 9
+    TASK t0_0 TYPE 0
+    TASK t0_1 TYPE 1
+    TASK t0_2 TYPE 1
+    TASK t0_3 TYPE 0
+    ARC a0_0 FROM t0_0 TO t0_1 TYPE 0
+    ARC a0_1 FROM t0_0 TO t0_2 TYPE 0
+    ARC a0_2 FROM t0_1 TO t0_3 TYPE 0
+    ARC a0_3 FROM t0_2 TO t0_3 TYPE 0
+}
+@CORE 0 {
+# price
+  1
+#----
+# type version execution_time
+  0    0       1
+  1    0       4
+}
+@CORE 1 {
+# price
+  1
+#----
+# type version execution_time
+  0    0       2
+  1    0       4
+}
+";
+
+    #[test]
+    fn check() {
+        let content = ::parse(INPUT).unwrap();
+        let platform = Platform::new(&content, "CORE");
+        let communication = Communication::new(1.0);
+        let ref graph = content.graphs[0];
+        let mut releases = HashMap::new();
+
+        macro_rules! entry(
+            ($task:expr, $element:expr, $start:expr, $finish:expr) => (Entry {
+                task: $task, element: $element, version: 0, start: $start, finish: $finish,
+            });
+        );
+
+        let schedule = super::heft(graph, &platform, &communication).unwrap();
+        assert!(super::check(&schedule, graph, &platform, &communication, &releases).is_empty());
+
+        let schedule = Schedule { entries: vec![
+            entry!(0, 0, -1.0, 0.0),
+            entry!(1, 0, 1.0, 5.0),
+            entry!(2, 0, 4.0, 8.0),
+            entry!(3, 2, 5.0, 6.0),
+            entry!(3, 0, 9.0, 10.0),
+            entry!(4, 0, 6.0, 7.0),
+        ]};
+        assert_eq!(super::check(&schedule, graph, &platform, &communication, &releases), vec![
+            Violation::Unexpected { task: 3 },
+            Violation::Unexpected { task: 4 },
+            Violation::Release { task: 0, release: 0.0, start: -1.0 },
+            Violation::Unsupported { task: 3, element: 2, version: 0 },
+            Violation::Precedence { arc: 2, ready: 6.0, start: 5.0 },
+            Violation::Precedence { arc: 3, ready: 9.0, start: 5.0 },
+            Violation::Overlap { element: 0, first: 1, second: 2 },
+        ]);
+
+        let schedule = Schedule { entries: vec![
+            entry!(0, 0, 0.0, 1.0),
+            entry!(1, 0, 1.0, 5.0),
+            entry!(2, 0, 5.0, 9.0),
+            entry!(3, 0, 9.0, 10.5),
+        ]};
+        assert_eq!(super::check(&schedule, graph, &platform, &communication, &releases), vec![
+            Violation::Duration { task: 3, expected: 1.0, actual: 1.5 },
+            Violation::Deadline { deadline: 0, task: 3, at: 9.0, finish: 10.5 },
+        ]);
+
+        let schedule = Schedule { entries: vec![entry!(0, 0, 0.0, 1.0)] };
+        assert_eq!(super::check(&schedule, graph, &platform, &communication, &releases).len(), 3);
+
+        let schedule = super::heft(graph, &platform, &communication).unwrap();
+        let start = schedule.find(2).unwrap().start;
+        releases.insert(2, start + 0.5);
+        assert_eq!(super::check(&schedule, graph, &platform, &communication, &releases), vec![
+            Violation::Release { task: 2, release: start + 0.5, start: start },
+        ]);
+        releases.insert(2, start);
+        assert!(super::check(&schedule, graph, &platform, &communication, &releases).is_empty());
+    }
+
+    #[test]
+    fn heft() {
+        let content = ::parse(INPUT).unwrap();
+        let platform = Platform::new(&content, "CORE");
+
+        let schedule = super::heft(&content.graphs[0], &platform,
+                                   &Communication::new(1.0)).unwrap();
+        assert_eq!(schedule.makespan(), 8.0);
+
+        let schedule = super::heft(&content.graphs[0], &platform,
+                                   &Communication::new(0.1)).unwrap();
+        assert_eq!(schedule.makespan(), 10.0);
+        assert_eq!(schedule.find(1).unwrap().element, 0);
+        assert_eq!(schedule.find(2).unwrap().element, 0);
+
+        let schedule = super::heft(&content.graphs[0], &platform,
+                                   &Communication::new(4.0)).unwrap();
+        assert_eq!(schedule.makespan(), 6.5);
+        assert_eq!(schedule.find(2).unwrap().start, 1.25);
+        assert_eq!(schedule.find(3).unwrap().element, 0);
+    }
+
+    #[test]
+    fn list() {
+        let content = ::parse(INPUT).unwrap();
+        let platform = Platform::new(&content, "CORE");
+        let communication = Communication::new(1.0);
+        let ref graph = content.graphs[0];
+        let releases = HashMap::new();
+
+        let schedule = super::heft(graph, &platform, &communication).unwrap();
+        let other = super::list(graph, &platform, &communication,
+                                &Mapping::from(&schedule)).unwrap();
+        assert_eq!(schedule.entries, other.entries);
+
+        let mut mapping = Mapping::from(&schedule);
+        for assignment in mapping.assignments.iter_mut() {
+            assignment.element = 0;
+            assignment.priority = 4 - assignment.priority;
+        }
+        let schedule = super::list(graph, &platform, &communication, &mapping).unwrap();
+        assert_eq!(schedule.find(2).unwrap().start, 1.0);
+        assert_eq!(schedule.makespan(), 10.0);
+        assert_eq!(super::check(&schedule, graph, &platform, &communication, &releases), vec![
+            Violation::Deadline { deadline: 0, task: 3, at: 9.0, finish: 10.0 },
+        ]);
+
+        mapping.assignments[0].version = 1;
+        assert!(super::list(graph, &platform, &communication, &mapping).is_none());
+    }
+
+    #[test]
+    fn insert() {
+        let slots = vec![(1.0, 2.0), (4.0, 5.0)];
+        assert_eq!(super::insert(&slots, 0.0, 1.0), 0.0);
+        assert_eq!(super::insert(&slots, 0.0, 2.0), 2.0);
+        assert_eq!(super::insert(&slots, 1.5, 3.0), 5.0);
+    }
+}
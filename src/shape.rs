@@ -0,0 +1,460 @@
+//! Generation of task graphs with well-known structures.
+//!
+//! Each task of a structured graph plays a role, such as a butterfly of a fast
+//! Fourier transform or a matrix multiplication of a tiled factorization.
+//! Task types are derived either from the roles, so that tasks doing the same
+//! work share a type, or drawn at random. In both cases, they stay below a
+//! given number of task types, so that the graphs can be used with existing
+//! data tables.
+
+use std::collections::HashMap;
+
+use Graph;
+use content::{new_arc, new_graph, new_task};
+use generator;
+use random::Generator;
+
+/// A configuration of structured graph generation.
+#[derive(Clone, Debug)]
+pub struct Config {
+    /// The name of the graph.
+    pub label: String,
+    /// The ID of the graph.
+    pub id: usize,
+    /// The number of task types.
+    pub task_types: usize,
+    /// The number of arc types.
+    pub arc_types: usize,
+    /// The assignment of types.
+    pub typing: Typing,
+    /// The seed of the random number generator used by random structures and
+    /// random types.
+    pub seed: u64,
+    /// The average time per task used to assign hard deadlines to the sinks,
+    /// if any. Deadlines are rounded to whole numbers of at least one.
+    pub transfer_time: Option<f64>,
+}
+
+/// An assignment of types to tasks and arcs.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Typing {
+    /// The type of a task is its role, and the type of an arc is the role of
+    /// its source, both modulo the number of types.
+    Role,
+    /// Types are drawn uniformly at random.
+    Random,
+}
+
+impl Default for Config {
+    fn default() -> Config {
+        Config {
+            label: String::from("TASK_GRAPH"),
+            id: 0,
+            task_types: 20,
+            arc_types: 20,
+            typing: Typing::Role,
+            seed: 0,
+            transfer_time: None,
+        }
+    }
+}
+
+struct Builder<'a> {
+    config: &'a Config,
+    generator: Generator,
+    roles: Vec<usize>,
+    arcs: Vec<(usize, usize)>,
+}
+
+impl<'a> Builder<'a> {
+    fn new(config: &'a Config) -> Builder<'a> {
+        Builder {
+            config: config,
+            generator: Generator::new(config.seed),
+            roles: vec![],
+            arcs: vec![],
+        }
+    }
+
+    fn task(&mut self, role: usize) -> usize {
+        self.roles.push(role);
+        self.roles.len() - 1
+    }
+
+    fn arc(&mut self, from: usize, to: usize) {
+        if !self.arcs.contains(&(from, to)) {
+            self.arcs.push((from, to));
+        }
+    }
+
+    fn build(mut self) -> Graph {
+        let task_types = self.config.task_types.max(1);
+        let arc_types = self.config.arc_types.max(1);
+        let mut graph = new_graph(self.config.label.clone(), self.config.id);
+        for (i, &role) in self.roles.iter().enumerate() {
+            let kind = match self.config.typing {
+                Typing::Role => role % task_types,
+                Typing::Random => self.generator.below(task_types),
+            };
+            graph.tasks.push(new_task(i, kind));
+        }
+        for (k, &(from, to)) in self.arcs.iter().enumerate() {
+            let kind = match self.config.typing {
+                Typing::Role => self.roles[from] % arc_types,
+                Typing::Random => self.generator.below(arc_types),
+            };
+            graph.arcs.push(new_arc(k, from, to, kind));
+        }
+        if let Some(time) = self.config.transfer_time {
+            generator::deadlines(&mut graph, time).unwrap();
+        }
+        graph
+    }
+}
+
+/// Generate the butterfly graph of a fast Fourier transform.
+///
+/// The graph has a layer of input tasks followed by `log2(points)` layers of
+/// butterfly tasks with one task per point in each layer. The roles are input
+/// and butterfly. The function returns `None` if the number of points is not
+/// a power of two.
+pub fn fft(points: usize, config: &Config) -> Option<Graph> {
+    if points == 0 || !points.is_power_of_two() {
+        return None;
+    }
+    let mut builder = Builder::new(config);
+    let mut previous = (0..points).map(|_| builder.task(0)).collect::<Vec<_>>();
+    let mut span = 1;
+    while span < points {
+        let current = (0..points).map(|_| builder.task(1)).collect::<Vec<_>>();
+        for i in 0..points {
+            builder.arc(previous[i], current[i]);
+            builder.arc(previous[i ^ span], current[i]);
+        }
+        previous = current;
+        span *= 2;
+    }
+    Some(builder.build())
+}
+
+/// Generate the graph of Gaussian elimination of a matrix.
+///
+/// Each step has a pivot task followed by one update task per remaining
+/// column, which gives `(size^2 + size - 2) / 2` tasks. The roles are pivot
+/// and update.
+pub fn gaussian(size: usize, config: &Config) -> Graph {
+    let mut builder = Builder::new(config);
+    let mut columns: Vec<Option<usize>> = vec![None; size];
+    for k in 0..size.saturating_sub(1) {
+        let pivot = builder.task(0);
+        if let Some(previous) = columns[k] {
+            builder.arc(previous, pivot);
+        }
+        for column in columns.iter_mut().skip(k + 1) {
+            let update = builder.task(1);
+            builder.arc(pivot, update);
+            if let Some(previous) = *column {
+                builder.arc(previous, update);
+            }
+            *column = Some(update);
+        }
+    }
+    builder.build()
+}
+
+/// Generate the graph of a tiled LU factorization.
+///
+/// The matrix has `blocks` by `blocks` tiles. The roles are the factorization
+/// of a diagonal tile, the triangular solves of a row and of a column, and
+/// the update of a trailing tile.
+pub fn lu(blocks: usize, config: &Config) -> Graph {
+    let mut tiles = Tiles::new(config);
+    for k in 0..blocks {
+        tiles.kernel(0, &[], (k, k));
+        for j in (k + 1)..blocks {
+            tiles.kernel(1, &[(k, k)], (k, j));
+        }
+        for i in (k + 1)..blocks {
+            tiles.kernel(2, &[(k, k)], (i, k));
+        }
+        for i in (k + 1)..blocks {
+            for j in (k + 1)..blocks {
+                tiles.kernel(3, &[(i, k), (k, j)], (i, j));
+            }
+        }
+    }
+    tiles.builder.build()
+}
+
+/// Generate the graph of a tiled Cholesky factorization.
+///
+/// The lower triangle of the matrix has `blocks` by `blocks` tiles. The roles
+/// are the factorization of a diagonal tile, the triangular solve, the
+/// symmetric update of a diagonal tile, and the update of an off-diagonal
+/// tile.
+pub fn cholesky(blocks: usize, config: &Config) -> Graph {
+    let mut tiles = Tiles::new(config);
+    for k in 0..blocks {
+        tiles.kernel(0, &[], (k, k));
+        for i in (k + 1)..blocks {
+            tiles.kernel(1, &[(k, k)], (i, k));
+        }
+        for i in (k + 1)..blocks {
+            tiles.kernel(2, &[(i, k)], (i, i));
+            for j in (k + 1)..i {
+                tiles.kernel(3, &[(i, k), (j, k)], (i, j));
+            }
+        }
+    }
+    tiles.builder.build()
+}
+
+/// Generate a fork-join graph.
+///
+/// A source task is followed by `stages` stages, each of which forks into
+/// `branches` parallel tasks that are joined by a single task. The roles are
+/// source, branch and join.
+pub fn fork_join(branches: usize, stages: usize, config: &Config) -> Graph {
+    let mut builder = Builder::new(config);
+    let mut last = builder.task(0);
+    for _ in 0..stages {
+        let forked = (0..branches).map(|_| builder.task(1)).collect::<Vec<_>>();
+        let join = builder.task(2);
+        for &branch in forked.iter() {
+            builder.arc(last, branch);
+            builder.arc(branch, join);
+        }
+        if forked.is_empty() {
+            builder.arc(last, join);
+        }
+        last = join;
+    }
+    builder.build()
+}
+
+/// Generate a random series-parallel graph.
+///
+/// The graph starts with two tasks connected by an arc and grows by series
+/// compositions, which insert a task after an existing one, and parallel
+/// compositions, which duplicate an inner task, until it has `count` tasks.
+/// The roles are source, sink and inner task.
+pub fn series_parallel(count: usize, config: &Config) -> Graph {
+    let mut builder = Builder::new(config);
+    if count == 0 {
+        return builder.build();
+    }
+    let source = builder.task(0);
+    if count == 1 {
+        return builder.build();
+    }
+    let sink = builder.task(1);
+    builder.arc(source, sink);
+
+    while builder.roles.len() < count {
+        let inner = (0..builder.roles.len()).filter(|&i| i != source && i != sink)
+                                            .collect::<Vec<_>>();
+        let task = builder.task(2);
+        if !inner.is_empty() && builder.generator.uniform() < 0.5 {
+            let original = inner[builder.generator.below(inner.len())];
+            let arcs = builder.arcs.clone();
+            for &(from, to) in arcs.iter() {
+                if to == original {
+                    builder.arc(from, task);
+                }
+                if from == original {
+                    builder.arc(task, to);
+                }
+            }
+        } else {
+            let candidates = builder.roles.len() - 2;
+            let original = match builder.generator.below(candidates) {
+                0 => source,
+                i => inner[i - 1],
+            };
+            for arc in builder.arcs.iter_mut() {
+                if arc.0 == original {
+                    arc.0 = task;
+                }
+            }
+            builder.arc(original, task);
+        }
+    }
+    builder.build()
+}
+
+/// Generate a random layered graph.
+///
+/// The tasks are split into `layers` layers of `width` tasks, and an arc goes
+/// from a task to a task of any later layer with probability `probability`.
+/// Every task that is not in the first layer and has no incoming arcs gets
+/// one from a random task of the preceding layer. The role of a task is its
+/// layer.
+pub fn layered(layers: usize, width: usize, probability: f64, config: &Config) -> Graph {
+    let mut builder = Builder::new(config);
+    let mut tasks = vec![];
+    for layer in 0..layers {
+        tasks.push((0..width).map(|_| builder.task(layer)).collect::<Vec<_>>());
+    }
+    for target in 1..layers {
+        for &to in tasks[target].iter() {
+            let mut connected = false;
+            for source in tasks[..target].iter() {
+                for &from in source.iter() {
+                    if builder.generator.uniform() < probability {
+                        builder.arc(from, to);
+                        connected = true;
+                    }
+                }
+            }
+            if !connected {
+                let from = tasks[target - 1][builder.generator.below(width)];
+                builder.arc(from, to);
+            }
+        }
+    }
+    builder.build()
+}
+
+/// Generate a software pipeline.
+///
+/// Each of `items` items passes through `stages` stages, and a stage processes
+/// the items in order, so that a task depends on the previous stage of the
+/// same item and on the same stage of the previous item. The role of a task is
+/// its stage.
+pub fn pipeline(stages: usize, items: usize, config: &Config) -> Graph {
+    let mut builder = Builder::new(config);
+    let mut previous: Vec<usize> = vec![];
+    for _ in 0..items {
+        let mut current = vec![];
+        for stage in 0..stages {
+            let task = builder.task(stage);
+            if stage > 0 {
+                builder.arc(current[stage - 1], task);
+            }
+            if let Some(&other) = previous.get(stage) {
+                builder.arc(other, task);
+            }
+            current.push(task);
+        }
+        previous = current;
+    }
+    builder.build()
+}
+
+/// Generate a one-dimensional three-point stencil.
+///
+/// Each of `steps` time steps has `width` tasks, and a task depends on the
+/// tasks of the previous step at the same position and at the adjacent ones.
+/// The roles are boundary and interior.
+pub fn stencil(width: usize, steps: usize, config: &Config) -> Graph {
+    let mut builder = Builder::new(config);
+    let mut previous: Vec<usize> = vec![];
+    for _ in 0..steps {
+        let current = (0..width).map(|x| {
+            builder.task(if x == 0 || x + 1 == width { 0 } else { 1 })
+        }).collect::<Vec<_>>();
+        for (x, &task) in current.iter().enumerate() {
+            for &other in previous.iter().take(x + 2).skip(x.saturating_sub(1)) {
+                builder.arc(other, task);
+            }
+        }
+        previous = current;
+    }
+    builder.build()
+}
+
+struct Tiles<'a> {
+    builder: Builder<'a>,
+    writers: HashMap<(usize, usize), usize>,
+}
+
+impl<'a> Tiles<'a> {
+    fn new(config: &'a Config) -> Tiles<'a> {
+        Tiles { builder: Builder::new(config), writers: HashMap::new() }
+    }
+
+    fn kernel(&mut self, role: usize, reads: &[(usize, usize)], write: (usize, usize)) {
+        let task = self.builder.task(role);
+        for tile in reads.iter().chain(Some(&write)) {
+            if let Some(&writer) = self.writers.get(tile) {
+                self.builder.arc(writer, task);
+            }
+        }
+        self.writers.insert(write, task);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use Graph;
+    use super::{Config, Typing};
+
+    fn summarize(graph: &Graph) -> (usize, usize, usize, usize) {
+        assert!(::dag::new(graph).is_some());
+        let sources = graph.tasks.iter().filter(|task| {
+            graph.arcs.iter().all(|arc| arc.to != task.id)
+        }).count();
+        let sinks = graph.tasks.iter().filter(|task| {
+            graph.arcs.iter().all(|arc| arc.from != task.id)
+        }).count();
+        (graph.tasks.len(), graph.arcs.len(), sources, sinks)
+    }
+
+    #[test]
+    fn fft() {
+        let config = Config::default();
+        assert_eq!(summarize(&super::fft(8, &config).unwrap()), (32, 48, 8, 8));
+        assert!(super::fft(6, &config).is_none());
+    }
+
+    #[test]
+    fn factorizations() {
+        let config = Config::default();
+        assert_eq!(summarize(&super::gaussian(5, &config)), (14, 19, 1, 1));
+        assert_eq!(summarize(&super::lu(3, &config)), (14, 21, 1, 1));
+        assert_eq!(summarize(&super::cholesky(3, &config)), (10, 12, 1, 1));
+    }
+
+    #[test]
+    fn fork_join() {
+        let graph = super::fork_join(3, 2, &Config::default());
+        assert_eq!(summarize(&graph), (9, 12, 1, 1));
+        assert_eq!(graph.tasks.iter().map(|task| task.kind).collect::<Vec<_>>(),
+                   vec![0, 1, 1, 1, 2, 1, 1, 1, 2]);
+    }
+
+    #[test]
+    fn series_parallel() {
+        let config = Config { seed: 3, ..Config::default() };
+        let graph = super::series_parallel(30, &config);
+        let (tasks, _, sources, sinks) = summarize(&graph);
+        assert_eq!((tasks, sources, sinks), (30, 1, 1));
+    }
+
+    #[test]
+    fn layered() {
+        let config = Config { typing: Typing::Random, task_types: 4, arc_types: 2,
+                              ..Config::default() };
+        let graph = super::layered(4, 5, 0.2, &config);
+        let (tasks, _, sources, _) = summarize(&graph);
+        assert_eq!((tasks, sources), (20, 5));
+        assert!(graph.tasks.iter().all(|task| task.kind < 4));
+        assert!(graph.arcs.iter().all(|arc| arc.kind < 2));
+    }
+
+    #[test]
+    fn pipeline() {
+        assert_eq!(summarize(&super::pipeline(3, 4, &Config::default())), (12, 17, 1, 1));
+    }
+
+    #[test]
+    fn stencil() {
+        let config = Config { transfer_time: Some(2.0), ..Config::default() };
+        let graph = super::stencil(4, 3, &config);
+        assert_eq!(summarize(&graph), (12, 20, 4, 4));
+        assert_eq!(graph.deadlines.iter().map(|deadline| (deadline.on, deadline.at))
+                                         .collect::<Vec<_>>(),
+                   vec![(8, 6), (9, 6), (10, 6), (11, 6)]);
+        assert_eq!(graph.attributes["PERIOD"], 6);
+    }
+}
@@ -0,0 +1,305 @@
+//! Self-contained SVG drawings.
+
+use std::fmt::Write;
+
+use {Content, Graph};
+use layout::Layout;
+use schedule::Schedule;
+
+const ARROW: &str = "  <defs><marker id=\"arrow\" viewBox=\"0 0 10 10\" refX=\"10\" refY=\"5\" \
+                     markerWidth=\"6\" markerHeight=\"6\" orient=\"auto\">\
+                     <path d=\"M 0 0 L 10 5 L 0 10 z\"/></marker></defs>";
+
+/// A configuration of a Gantt chart.
+#[derive(Clone, Debug)]
+pub struct Config {
+    /// The name of the tables describing the processing elements.
+    pub table: String,
+    /// The width of the time axis.
+    pub width: f64,
+    /// The height of a row.
+    pub row_height: f64,
+    /// The width of the labels of rows.
+    pub label_width: f64,
+    /// The gap between the drawing and its borders.
+    pub margin: f64,
+    /// The number of intervals of the time axis.
+    pub ticks: usize,
+}
+
+impl Default for Config {
+    fn default() -> Config {
+        Config {
+            table: String::from("CORE"),
+            width: 800.0,
+            row_height: 30.0,
+            label_width: 80.0,
+            margin: 20.0,
+            ticks: 10,
+        }
+    }
+}
+
+/// Draw a task graph given its layout.
+///
+/// A task is drawn as a box with its name and type, and a task with a hard
+/// deadline is drawn with a red border and its deadline. An arc is drawn as an
+/// arrow labeled with its type.
+pub fn graph(graph: &Graph, layout: &Layout) -> String {
+    let mut output = String::new();
+
+    header(&mut output, layout.width, layout.height);
+    writeln!(output, "  <title>{} {}</title>", escape(&graph.name), graph.id).unwrap();
+    writeln!(output, "{}", ARROW).unwrap();
+
+    for (arc, edge) in graph.arcs.iter().zip(layout.edges.iter()) {
+        let points = edge.points.iter().map(|&(x, y)| format!("{},{}", number(x), number(y)))
+                                       .collect::<Vec<_>>().join(" ");
+        writeln!(output, "  <polyline points=\"{}\" fill=\"none\" stroke=\"black\" \
+                          marker-end=\"url(#arrow)\"/>", points).unwrap();
+        let (x, y) = middle(&edge.points);
+        writeln!(output, "  <text x=\"{}\" y=\"{}\" dx=\"4\" fill=\"gray\">{}</text>", number(x),
+                 number(y), arc.kind).unwrap();
+    }
+
+    for (task, node) in graph.tasks.iter().zip(layout.nodes.iter()) {
+        let deadlines = graph.deadlines.iter().filter(|deadline| deadline.on == task.id)
+                                              .collect::<Vec<_>>();
+        let stroke = if deadlines.is_empty() { "black" } else { "red" };
+        writeln!(output, "  <rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" rx=\"4\" \
+                          fill=\"white\" stroke=\"{}\"/>", number(node.x - node.width / 2.0),
+                 number(node.y - node.height / 2.0), number(node.width), number(node.height),
+                 stroke).unwrap();
+        let mut lines = vec![format!("t{}_{}", graph.id, task.id), format!("type {}", task.kind)];
+        for deadline in deadlines {
+            lines.push(format!("deadline {}", deadline.at));
+        }
+        let top = node.y - 7.0 * (lines.len() as f64 - 1.0) + 4.0;
+        writeln!(output, "  <text x=\"{}\" y=\"{}\" text-anchor=\"middle\">", number(node.x),
+                 number(top)).unwrap();
+        for (i, line) in lines.iter().enumerate() {
+            writeln!(output, "    <tspan x=\"{}\" dy=\"{}\">{}</tspan>", number(node.x),
+                     if i == 0 { 0 } else { 14 }, line).unwrap();
+        }
+        writeln!(output, "  </text>").unwrap();
+    }
+
+    writeln!(output, "</svg>").unwrap();
+    output
+}
+
+/// Draw a schedule of a task graph as a Gantt chart.
+///
+/// Each processing element, which is a table called as specified in the
+/// configuration, has a row with the tasks scheduled onto it, which are
+/// labeled with their names and types. Arcs between tasks on different
+/// processing elements are drawn as arrows from the finish of the source to
+/// the start of the target, and hard deadlines as red marks in the rows of
+/// their tasks. The time axis spans the hyperperiod, the makespan and the
+/// deadlines, and the hyperperiod is marked by a dashed line.
+pub fn gantt(content: &Content, graph: &Graph, schedule: &Schedule, config: &Config) -> String {
+    let mut output = String::new();
+
+    let tables = content.tables.iter().filter(|table| table.name == config.table)
+                                      .collect::<Vec<_>>();
+    let rows = schedule.entries.iter().map(|entry| entry.element + 1)
+                                      .fold(tables.len(), usize::max);
+    let hyperperiod = content.attributes.get("HYPERPERIOD").map(|&period| period as f64);
+    let horizon = graph.deadlines.iter().map(|deadline| deadline.at as f64)
+                                        .chain(hyperperiod)
+                                        .fold(schedule.makespan(), f64::max);
+    let scale = if horizon > 0.0 { config.width / horizon } else { 0.0 };
+
+    let left = config.margin + config.label_width;
+    let top = config.margin;
+    let bottom = top + rows as f64 * config.row_height;
+    let x = |time: f64| left + time * scale;
+    let y = |row: usize| top + (row as f64 + 0.5) * config.row_height;
+    let width = left + config.width + config.margin;
+    let height = bottom + 20.0 + config.margin;
+
+    header(&mut output, width, height);
+    writeln!(output, "  <title>{} {}</title>", escape(&graph.name), graph.id).unwrap();
+    writeln!(output, "{}", ARROW).unwrap();
+
+    for row in 0..rows {
+        let label = match tables.get(row) {
+            Some(table) => format!("{} {}", escape(&table.name), table.id),
+            _ => format!("PE {}", row),
+        };
+        writeln!(output, "  <rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"{}\"/>",
+                 number(left), number(top + row as f64 * config.row_height),
+                 number(config.width), number(config.row_height),
+                 if row % 2 == 0 { "#f4f4f4" } else { "#e8e8e8" }).unwrap();
+        writeln!(output, "  <text x=\"{}\" y=\"{}\" dy=\"4\">{}</text>", number(config.margin),
+                 number(y(row)), label).unwrap();
+    }
+
+    writeln!(output, "  <line x1=\"{}\" y1=\"{}\" x2=\"{}\" y2=\"{}\" stroke=\"black\"/>",
+             number(left), number(bottom), number(left + config.width), number(bottom)).unwrap();
+    for i in 0..(config.ticks + 1) {
+        let time = horizon * i as f64 / config.ticks.max(1) as f64;
+        writeln!(output, "  <line x1=\"{}\" y1=\"{}\" x2=\"{}\" y2=\"{}\" stroke=\"black\"/>",
+                 number(x(time)), number(bottom), number(x(time)), number(bottom + 4.0)).unwrap();
+        writeln!(output, "  <text x=\"{}\" y=\"{}\" text-anchor=\"middle\">{}</text>",
+                 number(x(time)), number(bottom + 16.0), number(time)).unwrap();
+    }
+
+    for entry in schedule.entries.iter() {
+        let kind = graph.tasks.iter().find(|task| task.id == entry.task).map(|task| task.kind);
+        let label = match kind {
+            Some(kind) => format!("t{}_{} type {}", graph.id, entry.task, kind),
+            _ => format!("t{}_{}", graph.id, entry.task),
+        };
+        writeln!(output, "  <rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"white\" \
+                          stroke=\"black\"><title>{} [{}, {}]</title></rect>",
+                 number(x(entry.start)), number(y(entry.element) - config.row_height / 2.0 + 3.0),
+                 number((entry.finish - entry.start) * scale), number(config.row_height - 6.0),
+                 label, number(entry.start), number(entry.finish)).unwrap();
+        writeln!(output, "  <text x=\"{}\" y=\"{}\" dx=\"2\" dy=\"4\" font-size=\"10\">{}</text>",
+                 number(x(entry.start)), number(y(entry.element)), label).unwrap();
+    }
+
+    for arc in graph.arcs.iter() {
+        let (from, to) = match (schedule.find(arc.from), schedule.find(arc.to)) {
+            (Some(from), Some(to)) if from.element != to.element => (from, to),
+            _ => continue,
+        };
+        writeln!(output, "  <line x1=\"{}\" y1=\"{}\" x2=\"{}\" y2=\"{}\" stroke=\"blue\" \
+                          marker-end=\"url(#arrow)\"/>", number(x(from.finish)),
+                 number(y(from.element)), number(x(to.start)), number(y(to.element))).unwrap();
+    }
+
+    for deadline in graph.deadlines.iter() {
+        let entry = match schedule.find(deadline.on) {
+            Some(entry) => entry,
+            _ => continue,
+        };
+        let at = x(deadline.at as f64);
+        writeln!(output, "  <line x1=\"{}\" y1=\"{}\" x2=\"{}\" y2=\"{}\" stroke=\"red\" \
+                          stroke-width=\"2\"><title>t{}_{} deadline {}</title></line>",
+                 number(at), number(y(entry.element) - config.row_height / 2.0), number(at),
+                 number(y(entry.element) + config.row_height / 2.0), graph.id, deadline.on,
+                 deadline.at).unwrap();
+    }
+
+    if let Some(hyperperiod) = hyperperiod {
+        writeln!(output, "  <line x1=\"{}\" y1=\"{}\" x2=\"{}\" y2=\"{}\" stroke=\"gray\" \
+                          stroke-dasharray=\"4 4\"><title>hyperperiod {}</title></line>",
+                 number(x(hyperperiod)), number(top), number(x(hyperperiod)), number(bottom),
+                 number(hyperperiod)).unwrap();
+    }
+
+    writeln!(output, "</svg>").unwrap();
+    output
+}
+
+fn header(output: &mut String, width: f64, height: f64) {
+    writeln!(output, "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\" \
+                      viewBox=\"0 0 {} {}\" font-family=\"sans-serif\" font-size=\"12\">",
+             number(width), number(height), number(width), number(height)).unwrap();
+}
+
+fn middle(points: &[(f64, f64)]) -> (f64, f64) {
+    let i = (points.len() - 1) / 2;
+    ((points[i].0 + points[i + 1].0) / 2.0, (points[i].1 + points[i + 1].1) / 2.0)
+}
+
+fn number(value: f64) -> String {
+    let text = format!("{:.3}", value);
+    let text = text.trim_end_matches('0').trim_end_matches('.');
+    if text == "-0" { String::from("0") } else { text.to_string() }
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use layout::{self, Config};
+    use schedule::{Entry, Schedule};
+
+    #[test]
+    fn graph() {
+        let content = ::parse("@GRAPH 0 {
+    TASK t0_0 TYPE 3
+    TASK t0_1 TYPE 4
+    ARC a0_0 FROM t0_0 TO t0_1 TYPE 2
+    HARD_DEADLINE d0_0 ON t0_1 AT 9
+}
+").unwrap();
+        let graph = &content.graphs[0];
+        let layout = layout::new(graph, &Config::default()).unwrap();
+
+        let output = super::graph(graph, &layout);
+        assert!(output.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"120\" \
+                                    height=\"170\""));
+        assert!(output.contains("  <title>GRAPH 0</title>\n"));
+        assert!(output.contains("  <polyline points=\"60,60 60,110\" fill=\"none\""));
+        assert!(output.contains(">2</text>\n"));
+        assert!(output.contains("  <rect x=\"20\" y=\"20\" width=\"80\" height=\"40\" rx=\"4\" \
+                                 fill=\"white\" stroke=\"black\"/>\n"));
+        assert!(output.contains("stroke=\"red\"/>\n"));
+        assert!(output.contains("<tspan x=\"60\" dy=\"14\">type 4</tspan>\n"));
+        assert!(output.contains("<tspan x=\"60\" dy=\"14\">deadline 9</tspan>\n"));
+        assert!(output.ends_with("</svg>\n"));
+    }
+
+    #[test]
+    fn gantt() {
+        let content = ::parse("@HYPERPERIOD 10
+
+@GRAPH 0 {
+    TASK t0_0 TYPE 3
+    TASK t0_1 TYPE 4
+    TASK t0_2 TYPE 5
+    ARC a0_0 FROM t0_0 TO t0_1 TYPE 2
+    ARC a0_1 FROM t0_0 TO t0_2 TYPE 2
+    HARD_DEADLINE d0_0 ON t0_2 AT 8
+}
+
+@CORE 0 {
+# price
+  1
+
+#------------------------------------------------------------------------------
+# type version
+  0    0
+}
+
+@CORE 1 {
+# price
+  1
+
+#------------------------------------------------------------------------------
+# type version
+  0    0
+}
+").unwrap();
+        let graph = &content.graphs[0];
+        let schedule = Schedule { entries: vec![
+            Entry { task: 0, element: 0, version: 0, start: 0.0, finish: 2.5 },
+            Entry { task: 1, element: 0, version: 0, start: 2.5, finish: 4.0 },
+            Entry { task: 2, element: 1, version: 0, start: 3.0, finish: 5.0 },
+        ]};
+
+        let config = super::Config { ticks: 4, ..super::Config::default() };
+        let output = super::gantt(&content, graph, &schedule, &config);
+        assert!(output.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"920\" \
+                                    height=\"120\""));
+        assert!(output.contains(">CORE 0</text>\n"));
+        assert!(output.contains(">CORE 1</text>\n"));
+        assert!(output.contains("  <rect x=\"100\" y=\"23\" width=\"200\" height=\"24\" \
+                                 fill=\"white\" stroke=\"black\"><title>t0_0 type 3 [0, 2.5]\
+                                 </title></rect>\n"));
+        assert!(output.contains(">t0_2 type 5</text>\n"));
+        assert_eq!(output.matches("stroke=\"blue\"").count(), 1);
+        assert!(output.contains("  <line x1=\"300\" y1=\"35\" x2=\"340\" y2=\"65\" \
+                                 stroke=\"blue\""));
+        assert!(output.contains("  <line x1=\"740\" y1=\"50\" x2=\"740\" y2=\"80\" \
+                                 stroke=\"red\""));
+        assert!(output.contains("<title>hyperperiod 10</title>"));
+        assert!(output.contains(">7.5</text>\n"));
+    }
+}
@@ -0,0 +1,352 @@
+//! Thermal simulation of power profiles.
+//!
+//! Processing elements are rectangular blocks of a floorplan, and each block is
+//! a node of an RC network. A node has a thermal capacitance proportional to
+//! its area, a vertical thermal resistance to the ambient inversely
+//! proportional to its area, and lateral thermal resistances to the adjacent
+//! blocks. The network is integrated using the backward Euler method, which is
+//! stable for any sampling interval.
+
+use power::Profile;
+
+/// A floorplan of processing elements.
+#[derive(Clone, Debug)]
+pub struct Floorplan {
+    /// The blocks of the processing elements.
+    pub blocks: Vec<Block>,
+}
+
+/// A rectangular block of a floorplan.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Block {
+    /// The horizontal position of the left edge in meters.
+    pub x: f64,
+    /// The vertical position of the bottom edge in meters.
+    pub y: f64,
+    /// The width in meters.
+    pub width: f64,
+    /// The height in meters.
+    pub height: f64,
+}
+
+/// A configuration of the thermal model.
+#[derive(Clone, Debug)]
+pub struct Config {
+    /// The ambient temperature in kelvins.
+    pub ambient: f64,
+    /// The thickness of the die in meters.
+    pub thickness: f64,
+    /// The volumetric heat capacity of the die in J/(m³·K).
+    pub heat_capacity: f64,
+    /// The thermal conductivity of the die in W/(m·K).
+    pub conductivity: f64,
+    /// The area-specific thermal resistance between the die and the ambient in
+    /// K·m²/W, which accounts for the package and the heat sink.
+    pub vertical_resistance: f64,
+    /// The number of integration steps per sampling interval.
+    pub substeps: usize,
+}
+
+/// An RC thermal model.
+#[derive(Clone, Debug)]
+pub struct Model {
+    /// The number of processing elements.
+    pub elements: usize,
+    /// The ambient temperature.
+    pub ambient: f64,
+    /// The thermal capacitances of the nodes.
+    pub capacitance: Vec<f64>,
+    /// The thermal conductance matrix of the network in row-major order
+    /// including the conductances to the ambient on the diagonal.
+    pub conductance: Vec<f64>,
+    substeps: usize,
+}
+
+/// The outcome of a transient simulation.
+#[derive(Clone, Debug)]
+pub struct Transient {
+    /// The number of processing elements.
+    pub elements: usize,
+    /// The number of time steps.
+    pub steps: usize,
+    /// The temperatures at the end of each time step in column-major order.
+    pub data: Vec<f64>,
+    /// The peak temperature of each processing element.
+    pub peak: Vec<f64>,
+    /// The steady-state temperature of each processing element under the
+    /// average power of the profile.
+    pub steady: Vec<f64>,
+}
+
+impl Floorplan {
+    /// Create a floorplan with blocks of the same size arranged in a grid that
+    /// is as close to a square as possible.
+    pub fn grid(count: usize, width: f64, height: f64) -> Floorplan {
+        let columns = (count as f64).sqrt().ceil().max(1.0) as usize;
+        Floorplan {
+            blocks: (0..count).map(|i| Block {
+                x: (i % columns) as f64 * width,
+                y: (i / columns) as f64 * height,
+                width: width,
+                height: height,
+            }).collect(),
+        }
+    }
+}
+
+impl Block {
+    #[inline]
+    fn area(&self) -> f64 {
+        self.width * self.height
+    }
+
+    /// Return the length of the edge shared with another block.
+    pub fn shared(&self, other: &Block) -> f64 {
+        fn overlap(a: f64, b: f64, c: f64, d: f64) -> f64 {
+            (b.min(d) - a.max(c)).max(0.0)
+        }
+        fn touch(a: f64, b: f64) -> bool {
+            (a - b).abs() <= 1e-9 * a.abs().max(b.abs()).max(1e-3)
+        }
+        if touch(self.x + self.width, other.x) || touch(other.x + other.width, self.x) {
+            overlap(self.y, self.y + self.height, other.y, other.y + other.height)
+        } else if touch(self.y + self.height, other.y) || touch(other.y + other.height, self.y) {
+            overlap(self.x, self.x + self.width, other.x, other.x + other.width)
+        } else {
+            0.0
+        }
+    }
+}
+
+impl Default for Config {
+    fn default() -> Config {
+        Config {
+            ambient: 318.15,
+            thickness: 0.15e-3,
+            heat_capacity: 1.75e6,
+            conductivity: 100.0,
+            vertical_resistance: 8e-5,
+            substeps: 10,
+        }
+    }
+}
+
+impl Model {
+    /// Create a model of a floorplan.
+    ///
+    /// The function returns `None` if the vertical resistance is not positive
+    /// or if the centers of two adjacent blocks coincide, since the
+    /// corresponding conductances would be infinite.
+    pub fn new(floorplan: &Floorplan, config: &Config) -> Option<Model> {
+        if config.vertical_resistance <= 0.0 || config.vertical_resistance.is_nan() {
+            return None;
+        }
+
+        let ref blocks = floorplan.blocks;
+        let count = blocks.len();
+
+        let mut capacitance = vec![0.0; count];
+        let mut conductance = vec![0.0; count * count];
+        for i in 0..count {
+            capacitance[i] = config.heat_capacity * config.thickness * blocks[i].area();
+            conductance[i * count + i] += blocks[i].area() / config.vertical_resistance;
+            for j in (i + 1)..count {
+                let length = blocks[i].shared(&blocks[j]);
+                if length == 0.0 {
+                    continue;
+                }
+                let (dx, dy) = (blocks[i].x + blocks[i].width / 2.0 - blocks[j].x - blocks[j].width / 2.0,
+                                blocks[i].y + blocks[i].height / 2.0 - blocks[j].y - blocks[j].height / 2.0);
+                let distance = (dx * dx + dy * dy).sqrt();
+                if distance == 0.0 {
+                    return None;
+                }
+                let g = config.conductivity * config.thickness * length / distance;
+                conductance[i * count + i] += g;
+                conductance[j * count + j] += g;
+                conductance[i * count + j] -= g;
+                conductance[j * count + i] -= g;
+            }
+        }
+
+        Some(Model {
+            elements: count,
+            ambient: config.ambient,
+            capacitance: capacitance,
+            conductance: conductance,
+            substeps: config.substeps.max(1),
+        })
+    }
+
+    /// Compute the steady-state temperatures under constant power.
+    ///
+    /// The function returns `None` if the number of power values differs from
+    /// the number of processing elements of the model.
+    pub fn steady(&self, power: &[f64]) -> Option<Vec<f64>> {
+        if power.len() != self.elements {
+            return None;
+        }
+        let factor = cholesky(&self.conductance, self.elements);
+        let mut temperature = power.to_vec();
+        solve(&factor, self.elements, &mut temperature);
+        Some(temperature.iter().map(|&rise| self.ambient + rise).collect())
+    }
+
+    /// Simulate a power profile.
+    ///
+    /// The simulation starts from `initial` temperatures if given or from the
+    /// ambient temperature otherwise. The function returns `None` if the number
+    /// of processing elements in the profile differs from the one of the model.
+    pub fn simulate(&self, profile: &Profile, initial: Option<&[f64]>) -> Option<Transient> {
+        let count = self.elements;
+        if profile.elements != count {
+            return None;
+        }
+        if let Some(initial) = initial {
+            if initial.len() != count {
+                return None;
+            }
+        }
+
+        let step = profile.interval / self.substeps as f64;
+        let mut system = self.conductance.clone();
+        for i in 0..count {
+            system[i * count + i] += self.capacitance[i] / step;
+        }
+        let factor = cholesky(&system, count);
+
+        let mut rise = match initial {
+            Some(initial) => initial.iter().map(|&value| value - self.ambient).collect(),
+            _ => vec![0.0; count],
+        };
+        let mut data = Vec::with_capacity(count * profile.steps);
+        let mut average = vec![0.0; count];
+        for power in profile.steps().take(profile.steps) {
+            for _ in 0..self.substeps {
+                for i in 0..count {
+                    rise[i] = self.capacitance[i] / step * rise[i] + power[i];
+                }
+                solve(&factor, count, &mut rise);
+            }
+            for i in 0..count {
+                average[i] += power[i] / profile.steps as f64;
+                data.push(self.ambient + rise[i]);
+            }
+        }
+
+        let mut peak = vec![f64::NEG_INFINITY; count];
+        for temperature in data.chunks(if count > 0 { count } else { 1 }) {
+            for i in 0..count {
+                peak[i] = peak[i].max(temperature[i]);
+            }
+        }
+
+        Some(Transient {
+            elements: count,
+            steps: profile.steps,
+            data: data,
+            peak: peak,
+            steady: self.steady(&average)?,
+        })
+    }
+}
+
+/// Compute the lower-triangular Cholesky factor of a symmetric positive-definite
+/// matrix stored in row-major order.
+fn cholesky(matrix: &[f64], count: usize) -> Vec<f64> {
+    let mut factor = vec![0.0; count * count];
+    for i in 0..count {
+        for j in 0..(i + 1) {
+            let mut sum = matrix[i * count + j];
+            for k in 0..j {
+                sum -= factor[i * count + k] * factor[j * count + k];
+            }
+            factor[i * count + j] = if i == j { sum.sqrt() } else { sum / factor[j * count + j] };
+        }
+    }
+    factor
+}
+
+/// Solve a linear system given the Cholesky factor of its matrix.
+fn solve(factor: &[f64], count: usize, vector: &mut [f64]) {
+    for i in 0..count {
+        let mut sum = vector[i];
+        for k in 0..i {
+            sum -= factor[i * count + k] * vector[k];
+        }
+        vector[i] = sum / factor[i * count + i];
+    }
+    for i in (0..count).rev() {
+        let mut sum = vector[i];
+        for k in (i + 1)..count {
+            sum -= factor[k * count + i] * vector[k];
+        }
+        vector[i] = sum / factor[i * count + i];
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use power::Profile;
+    use super::{Block, Config, Floorplan, Model};
+
+    #[test]
+    fn grid() {
+        let floorplan = Floorplan::grid(3, 2.0, 1.0);
+        assert_eq!(floorplan.blocks[1].x, 2.0);
+        assert_eq!(floorplan.blocks[2].y, 1.0);
+        assert_eq!(floorplan.blocks[0].shared(&floorplan.blocks[1]), 1.0);
+        assert_eq!(floorplan.blocks[0].shared(&floorplan.blocks[2]), 2.0);
+        assert_eq!(floorplan.blocks[1].shared(&floorplan.blocks[2]), 0.0);
+    }
+
+    #[test]
+    fn new() {
+        let floorplan = Floorplan::grid(2, 4e-3, 4e-3);
+        let config = Config { vertical_resistance: 0.0, ..Config::default() };
+        assert!(Model::new(&floorplan, &config).is_none());
+
+        let block = Block { x: 0.0, y: 0.0, width: 0.0, height: 4e-3 };
+        let floorplan = Floorplan { blocks: vec![block, block] };
+        assert!(Model::new(&floorplan, &Config::default()).is_none());
+    }
+
+    #[test]
+    fn steady() {
+        let floorplan = Floorplan::grid(2, 4e-3, 4e-3);
+        let model = Model::new(&floorplan, &Config::default()).unwrap();
+
+        let temperature = model.steady(&[10.0, 0.0]).unwrap();
+        assert!(temperature[0] > temperature[1]);
+        assert!(temperature[1] > model.ambient);
+
+        let temperature = model.steady(&[10.0, 10.0]).unwrap();
+        assert!((temperature[0] - 318.15 - 50.0).abs() < 1e-9);
+        assert!((temperature[1] - 318.15 - 50.0).abs() < 1e-9);
+
+        assert!(model.steady(&[10.0]).is_none());
+    }
+
+    #[test]
+    fn simulate() {
+        let floorplan = Floorplan::grid(2, 4e-3, 4e-3);
+        let model = Model::new(&floorplan, &Config::default()).unwrap();
+        let profile = Profile {
+            elements: 2,
+            steps: 1000,
+            interval: 1e-2,
+            data: vec![10.0; 2000],
+        };
+
+        let transient = model.simulate(&profile, None).unwrap();
+        assert_eq!(transient.data.len(), 2000);
+        assert!(transient.data[0] > model.ambient);
+        assert!(transient.data[0] < transient.data[1998]);
+        assert!((transient.peak[0] - transient.steady[0]).abs() < 1e-3);
+
+        let transient = model.simulate(&profile, Some(&transient.steady)).unwrap();
+        assert!((transient.data[0] - transient.steady[0]).abs() < 1e-9);
+
+        assert!(model.simulate(&profile, Some(&[0.0])).is_none());
+    }
+}
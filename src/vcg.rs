@@ -0,0 +1,316 @@
+//! VCG descriptions of task graphs.
+//!
+//! A task is written as a node titled after the task as in TGFF files, such as
+//! `t0_1`, and labeled with its name, its type and its hard deadline if any.
+//! An arc is written as an edge labeled with its type. The graph is titled
+//! after its name and ID, such as `GRAPH_0`, and labeled with its period.
+
+use std::collections::HashMap;
+use std::fmt::Write;
+
+use {Content, Error, Graph, Result};
+use content::{self, new_arc, new_deadline, new_graph, new_task};
+use generator::gcd;
+use lexer::{escape, find, natural, Attributes, Lexer, Token};
+
+/// Write a task graph in the VCG format.
+pub fn write(graph: &Graph) -> String {
+    let mut output = String::new();
+    let name = |id: usize| format!("t{}_{}", graph.id, id);
+
+    writeln!(output, "graph: {{").unwrap();
+    writeln!(output, "  title: \"{}_{}\"", escape(&graph.name), graph.id).unwrap();
+    match graph.attributes.get("PERIOD") {
+        Some(&period) => writeln!(output, "  label: \"{} {} (period {})\"", escape(&graph.name),
+                                  graph.id, period).unwrap(),
+        _ => writeln!(output, "  label: \"{} {}\"", escape(&graph.name), graph.id).unwrap(),
+    }
+    writeln!(output, "  display_edge_labels: yes").unwrap();
+
+    for task in graph.tasks.iter() {
+        let mut label = format!("{}\\ntype {}", name(task.id), task.kind);
+        let mut attributes = String::new();
+        for deadline in graph.deadlines.iter().filter(|deadline| deadline.on == task.id) {
+            write!(label, "\\ndeadline {}", deadline.at).unwrap();
+            attributes = String::from(" bordercolor: red borderwidth: 3");
+        }
+        writeln!(output, "  node: {{ title: \"{}\" label: \"{}\"{} }}", name(task.id), label,
+                 attributes).unwrap();
+    }
+    for arc in graph.arcs.iter() {
+        writeln!(output, "  edge: {{ sourcename: \"{}\" targetname: \"{}\" label: \"{}\" }}",
+                 name(arc.from), name(arc.to), arc.kind).unwrap();
+    }
+
+    writeln!(output, "}}").unwrap();
+    output
+}
+
+/// Read task graphs from a VCG description.
+///
+/// Each graph or subgraph that contains nodes is read as a separate task
+/// graph, and subgraphs without nodes, such as the top-level graph wrapping
+/// several task graphs, are only traversed. Tasks are numbered in the order
+/// in which their nodes appear, and arcs in the order of their edges. The
+/// types and deadlines of tasks are taken from the `type` and `deadline` lines
+/// of the labels of nodes, the types of arcs from the labels of edges, and the
+/// periods of graphs from the labels of graphs. A number in parentheses at the
+/// end of a label, as in `t0_1 (1)` or `a0_0 (2)`, is also taken as the type
+/// of the task or arc. Tasks and arcs without types get type zero. If any
+/// period is found, the hyperperiod is set to the least common multiple of the
+/// periods. If the title of a graph ends with an underscore followed by a
+/// number, as in `GRAPH_0`, the number is taken as the ID of the graph.
+/// Attributes other than titles and labels are skipped.
+pub fn read(input: &str) -> Result<Content> {
+    let mut lexer = Lexer::new(input);
+    let mut content = content::new();
+
+    if lexer.value()? != "graph" {
+        return Err(lexer.error(String::from("expected `graph`")));
+    }
+    lexer.expect(':')?;
+    lexer.expect('{')?;
+    block(&mut lexer, &mut content)?;
+    if lexer.next()?.is_some() {
+        return Err(lexer.error(String::from("found an unexpected statement")));
+    }
+
+    let mut hyperperiod = None;
+    for graph in content.graphs.iter() {
+        if let Some(&period) = graph.attributes.get("PERIOD") {
+            hyperperiod = Some(match hyperperiod {
+                Some(hyperperiod) => hyperperiod / gcd(hyperperiod, period) * period,
+                _ => period,
+            });
+        }
+    }
+    if let Some(hyperperiod) = hyperperiod {
+        content.attributes.insert(String::from("HYPERPERIOD"), hyperperiod);
+    }
+
+    Ok(content)
+}
+
+fn block(lexer: &mut Lexer, content: &mut Content) -> Result<()> {
+    let mut title = None;
+    let mut label = None;
+    let mut nodes = vec![];
+    let mut edges = vec![];
+
+    loop {
+        let key = match lexer.next()? {
+            Some(Token::Symbol('}')) => break,
+            Some(Token::Word(key)) => key,
+            Some(_) => return Err(lexer.error(String::from("found an unexpected token"))),
+            _ => return Err(lexer.error(String::from("expected `}`"))),
+        };
+        lexer.expect(':')?;
+        match &key[..] {
+            "graph" => {
+                lexer.expect('{')?;
+                block(lexer, content)?;
+            },
+            "node" => nodes.push((object(lexer)?, lexer.line)),
+            "edge" | "nearedge" | "bentnearedge" | "backedge" => {
+                edges.push((object(lexer)?, lexer.line));
+            },
+            "title" => title = Some(lexer.value()?),
+            "label" => label = Some((lexer.value()?, lexer.line)),
+            _ => skip(lexer)?,
+        }
+    }
+    if nodes.is_empty() {
+        return Ok(());
+    }
+
+    let (name, id) = ::lexer::title(title);
+    let mut graph = new_graph(name, id);
+    if let Some((label, line)) = label {
+        if let Some(start) = label.find("(period ") {
+            let value = label[(start + 8)..].split(')').next().unwrap();
+            graph.attributes.insert(String::from("PERIOD"), natural(value, "period", line)?);
+        }
+    }
+
+    let mut index = HashMap::new();
+    for (i, &(ref attributes, line)) in nodes.iter().enumerate() {
+        let title = match find(attributes, "title").map(|attribute| &attribute.1) {
+            Some(title) => title,
+            _ => raise!(line, "found a node without a title"),
+        };
+        if index.insert(title.to_string(), i).is_some() {
+            raise!(line, "found a duplicate node `{}`", title);
+        }
+        let mut kind = 0;
+        if let Some(label) = find(attributes, "label").map(|attribute| &attribute.1) {
+            for part in label.split("\\n").flat_map(|part| part.lines()) {
+                let mut words = part.split_whitespace();
+                match (words.next(), words.next(), words.next()) {
+                    (Some("type"), Some(value), None) => kind = natural(value, "type", line)?,
+                    (Some("deadline"), Some(value), None) => {
+                        let at = natural(value, "deadline", line)?;
+                        let id = graph.deadlines.len();
+                        graph.deadlines.push(new_deadline(id, i, at));
+                    },
+                    _ => if let Some(value) = suffix(part) {
+                        kind = natural(value, "type", line)?;
+                    },
+                }
+            }
+        }
+        graph.tasks.push(new_task(i, kind));
+    }
+    for (k, &(ref attributes, line)) in edges.iter().enumerate() {
+        let mut ends = vec![];
+        for name in &["sourcename", "targetname"] {
+            match find(attributes, name).map(|attribute| &attribute.1) {
+                Some(title) => match index.get(title) {
+                    Some(&i) => ends.push(i),
+                    _ => raise!(line, "found an unknown node `{}`", title),
+                },
+                _ => raise!(line, "found an edge without a `{}`", name),
+            }
+        }
+        let kind = match find(attributes, "label").map(|attribute| &attribute.1) {
+            Some(label) => match suffix(label) {
+                Some(value) => natural(value, "type", line)?,
+                _ => natural(label.trim(), "label", line)?,
+            },
+            _ => 0,
+        };
+        graph.arcs.push(new_arc(k, ends[0], ends[1], kind));
+    }
+    content.graphs.push(graph);
+
+    Ok(())
+}
+
+fn suffix(label: &str) -> Option<&str> {
+    let label = label.trim();
+    if !label.ends_with(')') {
+        return None;
+    }
+    label.rfind('(').map(|start| label[(start + 1)..(label.len() - 1)].trim())
+}
+
+fn object(lexer: &mut Lexer) -> Result<Attributes> {
+    let mut attributes = vec![];
+    lexer.expect('{')?;
+    while !lexer.accept('}')? {
+        let name = lexer.value()?;
+        lexer.expect(':')?;
+        if lexer.peek()? == Some(&Token::Symbol('{')) {
+            skip(lexer)?;
+            continue;
+        }
+        attributes.push((name, lexer.value()?, lexer.line));
+    }
+    Ok(attributes)
+}
+
+fn skip(lexer: &mut Lexer) -> Result<()> {
+    if !lexer.accept('{')? {
+        lexer.value()?;
+        return Ok(());
+    }
+    let mut depth = 1;
+    while depth > 0 {
+        match lexer.next()? {
+            Some(Token::Symbol('{')) => depth += 1,
+            Some(Token::Symbol('}')) => depth -= 1,
+            Some(_) => {},
+            _ => return Err(lexer.error(String::from("expected `}`"))),
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    const INPUT: &str = "@GRAPH 0 {
+    PERIOD 10
+    TASK t0_0 TYPE 3
+    TASK t0_1 TYPE 4
+    TASK t0_2 TYPE 5
+    ARC a0_0 FROM t0_0 TO t0_1 TYPE 0
+    ARC a0_1 FROM t0_0 TO t0_2 TYPE 1
+    HARD_DEADLINE d0_0 ON t0_2 AT 9
+}
+";
+
+    #[test]
+    fn write() {
+        let content = ::parse(INPUT).unwrap();
+        assert_eq!(super::write(&content.graphs[0]), "graph: {
+  title: \"GRAPH_0\"
+  label: \"GRAPH 0 (period 10)\"
+  display_edge_labels: yes
+  node: { title: \"t0_0\" label: \"t0_0\\ntype 3\" }
+  node: { title: \"t0_1\" label: \"t0_1\\ntype 4\" }
+  node: { title: \"t0_2\" label: \"t0_2\\ntype 5\\ndeadline 9\" bordercolor: red borderwidth: 3 }
+  edge: { sourcename: \"t0_0\" targetname: \"t0_1\" label: \"0\" }
+  edge: { sourcename: \"t0_0\" targetname: \"t0_2\" label: \"1\" }
+}
+");
+    }
+
+    #[test]
+    fn read() {
+        let content = ::parse(INPUT).unwrap();
+        let result = super::read(&super::write(&content.graphs[0])).unwrap();
+        assert_eq!(result.attributes["HYPERPERIOD"], 10);
+        let graph = &result.graphs[0];
+        assert_eq!((&graph.name[..], graph.id), ("GRAPH", 0));
+        assert_eq!(graph.attributes["PERIOD"], 10);
+        assert_eq!(graph.tasks.iter().map(|task| task.kind).collect::<Vec<_>>(), &[3, 4, 5]);
+        assert_eq!(graph.arcs.iter().map(|arc| (arc.from, arc.to, arc.kind)).collect::<Vec<_>>(),
+                   &[(0, 1, 0), (0, 2, 1)]);
+        assert_eq!((graph.deadlines[0].on, graph.deadlines[0].at), (2, 9));
+
+        let result = super::read("graph: {
+  title: \"TGFF\" // a wrapper
+  orientation: top_to_bottom
+  graph: { title: \"TASK_GRAPH_1\" label: \"TASK_GRAPH 1 (period 6)\"
+    node: { title: \"a\" label: \"a\" loc: { x: 10 y: 20 } }
+    node: { title: \"b\" label: \"b\\ntype 2\" }
+    bentnearedge: { sourcename: \"a\" targetname: \"b\" }
+  }
+  graph: { title: \"other\"
+    node: { title: \"a\" }
+  }
+}
+").unwrap();
+        assert_eq!(result.graphs.len(), 2);
+        assert_eq!((&result.graphs[0].name[..], result.graphs[0].id), ("TASK_GRAPH", 1));
+        assert_eq!(result.attributes["HYPERPERIOD"], 6);
+        assert_eq!(result.graphs[0].attributes["PERIOD"], 6);
+        assert_eq!(result.graphs[0].tasks[1].kind, 2);
+        assert_eq!(result.graphs[0].arcs[0].kind, 0);
+        assert_eq!((&result.graphs[1].name[..], result.graphs[1].id), ("other", 0));
+
+        let result = super::read("graph: {
+display_edge_labels: yes
+title: \"TASK_GRAPH 0\"
+node: { title: \"t0_0\" label: \"t0_0 (0)\" }
+node: { title: \"t0_1\" label: \"t0_1 (1)\" }
+edge: { sourcename: \"t0_0\" targetname: \"t0_1\" label: \"a0_0 (2)\" }
+}
+").unwrap();
+        let graph = &result.graphs[0];
+        assert_eq!((&graph.name[..], graph.id), ("TASK_GRAPH 0", 0));
+        assert!(result.attributes.is_empty());
+        assert_eq!(graph.tasks.iter().map(|task| task.kind).collect::<Vec<_>>(), &[0, 1]);
+        assert_eq!((graph.arcs[0].from, graph.arcs[0].to, graph.arcs[0].kind), (0, 1, 2));
+
+        for label in &["a0_0 (two)", "two"] {
+            let error = super::read(&format!("graph: {{\n  node: {{ title: \"a\" }}\n  edge: {{ \
+sourcename: \"a\" targetname: \"a\" label: \"{}\" }}\n}}", label)).err().unwrap();
+            assert_eq!(error.line, 3);
+        }
+
+        let error = super::read("graph: {\n  node: { title: \"a\" }\n  edge: { sourcename: \"a\" \
+targetname: \"b\" }\n}").err().unwrap();
+        assert_eq!(error.line, 3);
+        assert_eq!(error.message, "found an unknown node `b`");
+    }
+}
@@ -2,6 +2,9 @@ extern crate assert;
 
 extern crate tgff;
 
+#[cfg(feature = "serde")]
+extern crate serde_json;
+
 #[test]
 fn parse_002_040() {
     let result = tgff::parse(&read_fixture("002_040.tgff")).unwrap();
@@ -66,6 +69,176 @@ fn parse_032_640() {
     }
 }
 
+#[test]
+fn heft_032_640() {
+    use std::collections::HashMap;
+
+    use tgff::communication::Communication;
+    use tgff::platform::Platform;
+
+    let content = tgff::parse(&read_fixture("032_640.tgff")).unwrap();
+    let platform = Platform::new(&content, "CORE");
+    let communication = Communication::new(100.0);
+
+    let schedule = tgff::schedule::heft(&content.graphs[0], &platform, &communication).unwrap();
+    assert_eq!(schedule.entries.len(), 640);
+    assert!(tgff::schedule::check(&schedule, &content.graphs[0], &platform, &communication,
+                                  &HashMap::new()).is_empty());
+
+    let config = tgff::svg::Config::default();
+    let output = tgff::svg::gantt(&content, &content.graphs[0], &schedule, &config);
+    assert_eq!(output.matches("</title></rect>").count(), 640);
+}
+
+#[test]
+fn evolve_032_640() {
+    use std::collections::HashMap;
+
+    use tgff::communication::Communication;
+    use tgff::genetic::{self, Config};
+    use tgff::optimization::{Makespan, Problem};
+    use tgff::platform::Platform;
+
+    let content = tgff::parse(&read_fixture("032_640.tgff")).unwrap();
+    let platform = Platform::new(&content, "CORE");
+    let communication = Communication::new(100.0);
+    let problem = Problem::new(&content.graphs[0], &platform, &communication);
+
+    let config = Config { population: 8, generations: 3, threads: 4, ..Config::default() };
+    let solution = genetic::evolve(&problem, &Makespan, &config).unwrap();
+    assert!(solution.cost <= tgff::schedule::heft(problem.graph, &platform,
+                                                  &communication).unwrap().makespan());
+    assert!(tgff::schedule::check(&solution.schedule, problem.graph, &platform, &communication,
+                                  &HashMap::new()).is_empty());
+}
+
+#[test]
+fn options_032_640() {
+    use tgff::options::{Command, Parameter, Statement};
+
+    let options = tgff::options::parse(&read_fixture("032_640.tgffopt")).unwrap();
+    assert_eq!(options.statements.len(), 16);
+    assert_eq!(options.statements[11], Statement::Command(Command::EpsWrite));
+    let attributes = options.find(|parameter| match *parameter {
+        Parameter::TypeAttributes(ref attributes) => Some(attributes.clone()),
+        _ => None,
+    }).unwrap();
+    assert_eq!(attributes.len(), 2);
+    assert_eq!(attributes[1].name, "execution_time");
+    assert_eq!(tgff::options::parse(&options.to_string()).unwrap(), options);
+
+    let content = tgff::generator::generate(&options.graphs());
+    assert_eq!(content.graphs.len(), 1);
+    assert_eq!(content.graphs[0].name, "GRAPH");
+    assert_eq!(content.graphs[0].tasks.len(), 640);
+
+    let tables = tgff::generator::tables(&options.tables());
+    assert_eq!(tables.len(), 32);
+    for table in tables.iter() {
+        assert_eq!(table.name, "CORE");
+        assert_eq!(table.columns.len(), 4);
+        assert_eq!(table.columns[3].name, "execution_time");
+        assert_eq!(table.columns[3].data.len(), 320);
+    }
+}
+
+#[test]
+fn shape_002_040() {
+    use std::collections::HashMap;
+
+    use tgff::communication::Communication;
+    use tgff::platform::Platform;
+    use tgff::shape::{self, Config};
+
+    let content = tgff::parse(&read_fixture("002_040.tgff")).unwrap();
+    let platform = Platform::new(&content, "CORE");
+    let communication = Communication::new(100.0);
+
+    let config = Config { label: String::from("GRAPH"), transfer_time: Some(0.05),
+                          ..Config::default() };
+    let graph = shape::cholesky(4, &config);
+    assert_eq!(graph.tasks.len(), 20);
+    assert_eq!(graph.deadlines.len(), 1);
+
+    let schedule = tgff::schedule::heft(&graph, &platform, &communication).unwrap();
+    assert!(tgff::schedule::check(&schedule, &graph, &platform, &communication,
+                                  &HashMap::new()).is_empty());
+}
+
+#[test]
+fn layout_032_640() {
+    use tgff::layout::{self, Config};
+
+    let content = tgff::parse(&read_fixture("032_640.tgff")).unwrap();
+    let graph = &content.graphs[0];
+    let config = Config::default();
+
+    let layout = layout::new(graph, &config).unwrap();
+    assert_eq!(layout.nodes.len(), 640);
+    assert_eq!(layout.edges.len(), 848);
+    let mut nodes = layout.nodes.clone();
+    nodes.sort_by(|one, other| (one.layer, one.x).partial_cmp(&(other.layer, other.x)).unwrap());
+    for pair in nodes.windows(2).filter(|pair| pair[0].layer == pair[1].layer) {
+        assert!(pair[1].x - pair[0].x >= config.node_width + config.node_spacing - 1e-9);
+    }
+
+    let output = tgff::svg::graph(graph, &layout);
+    assert_eq!(output.matches("<rect ").count(), 640);
+    assert_eq!(output.matches("<polyline ").count(), 848);
+}
+
+#[test]
+fn csv_032_640() {
+    use tgff::csv::{self, Config};
+
+    let content = tgff::parse(&read_fixture("032_640.tgff")).unwrap();
+    let config = Config::default();
+    for table in content.tables.iter() {
+        let output = csv::write(table, &config);
+        assert_eq!(output.lines().count(), 1 + 1 + 320);
+        assert_eq!(&csv::read(&output, &table.name, table.id, &config).unwrap(), table);
+    }
+}
+
+#[test]
+fn graphml_032_640() {
+    use tgff::graphml;
+
+    let content = tgff::parse(&read_fixture("032_640.tgff")).unwrap();
+    let result = graphml::read(&graphml::write(&content)).unwrap();
+    assert_eq!(result.attributes, content.attributes);
+    assert_eq!(result.graphs, content.graphs);
+}
+
+#[test]
+fn write_032_640() {
+    let content = tgff::parse(&read_fixture("032_640.tgff")).unwrap();
+    assert_eq!(tgff::parse(&content.to_string()).unwrap(), content);
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn serde_002_040() {
+    let content = tgff::parse(&read_fixture("002_040.tgff")).unwrap();
+    let json = serde_json::to_string(&content).unwrap();
+    assert!(json.starts_with("{\"attributes\":{\"HYPERPERIOD\":8},\"graphs\":[{\"name\":\"GRAPH\",\
+                              \"id\":0,\"attributes\":{\"PERIOD\":8},\"tasks\":[{\"id\":0,\"kind\":"));
+    assert_eq!(serde_json::from_str::<tgff::Content>(&json).unwrap(), content);
+
+    let input = read_fixture("002_040.tgff");
+    assert_eq!(tgff::from_str::<tgff::Content>(&input).unwrap(), content);
+    assert_eq!(tgff::parse(&tgff::to_string(&content).unwrap()).unwrap(), content);
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn serde_032_640() {
+    let content = tgff::parse(&read_fixture("032_640.tgff")).unwrap();
+    let json = serde_json::to_string(&content).unwrap();
+    assert_eq!(serde_json::to_string(&content.clone()).unwrap(), json);
+    assert_eq!(serde_json::from_str::<tgff::Content>(&json).unwrap(), content);
+}
+
 fn read_fixture(name: &str) -> String {
     use std::fs::File;
     use std::io::Read;